@@ -1,6 +1,7 @@
 use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
-use syn::{DeriveInput, LitChar, parse_macro_input};
+use syn::{Attribute, Data, DeriveInput, Fields, Ident, LitChar, LitStr, parse_macro_input};
 
 #[proc_macro_derive(Cellable, attributes(token))]
 pub fn derive_parse_cell(input: TokenStream) -> TokenStream {
@@ -52,3 +53,172 @@ pub fn derive_parse_cell(input: TokenStream) -> TokenStream {
 
     out.into()
 }
+
+/// A format string such as `"{x},{y}"`, split into the literal text around its placeholders.
+struct Pattern {
+    text: String,
+    literals: Vec<String>,
+    fields: Vec<String>,
+}
+
+impl Pattern {
+    fn from_attrs(attrs: &[Attribute], owner: &Ident) -> syn::Result<Self> {
+        let Some(attr) = attrs.iter().find(|a| a.path().is_ident("pattern")) else {
+            return Err(syn::Error::new_spanned(
+                owner,
+                "missing #[pattern(\"...\")] attribute",
+            ));
+        };
+        let lit = attr.parse_args::<LitStr>()?;
+        let text = lit.value();
+
+        let mut literals = vec![String::new()];
+        let mut fields = vec![];
+        let mut chars = text.chars().peekable();
+        while let Some(ch) = chars.next() {
+            match ch {
+                '{' | '}' if chars.peek() == Some(&ch) => {
+                    chars.next();
+                    literals.last_mut().unwrap().push(ch);
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(syn::Error::new_spanned(&lit, "unclosed `{`")),
+                        }
+                    }
+                    // Without a separator there's no telling where one field stops
+                    if !fields.is_empty() && literals.last().unwrap().is_empty() {
+                        return Err(syn::Error::new_spanned(
+                            &lit,
+                            "placeholders must be separated by some text",
+                        ));
+                    }
+                    fields.push(name.trim().to_string());
+                    literals.push(String::new());
+                }
+                '}' => return Err(syn::Error::new_spanned(&lit, "unmatched `}`")),
+                c => literals.last_mut().unwrap().push(c),
+            }
+        }
+
+        Ok(Self {
+            text,
+            literals,
+            fields,
+        })
+    }
+
+    /// An `if` that returns `ctor` built from the captures when the pattern matches `s`.
+    fn matcher(
+        &self,
+        ctor: TokenStream2,
+        fields: &Fields,
+        owner: &Ident,
+    ) -> syn::Result<TokenStream2> {
+        let names = match fields {
+            Fields::Named(named) => named
+                .named
+                .iter()
+                .map(|f| f.ident.as_ref().unwrap().to_string())
+                .collect::<Vec<_>>(),
+            Fields::Unnamed(unnamed) => (0..unnamed.unnamed.len()).map(|i| i.to_string()).collect(),
+            Fields::Unit => vec![],
+        };
+
+        let mut sorted = self.fields.clone();
+        sorted.sort();
+        let mut expected = names.clone();
+        expected.sort();
+        if sorted != expected {
+            return Err(syn::Error::new_spanned(
+                owner,
+                format!(
+                    "pattern {:?} must use each of the fields {{{}}} exactly once",
+                    self.text,
+                    names.join("}, {")
+                ),
+            ));
+        }
+
+        let values = names.iter().map(|name| {
+            let i = self.fields.iter().position(|f| f == name).unwrap();
+            quote! { ::parse::field(#name, caps[#i])? }
+        });
+        let body = match fields {
+            Fields::Named(named) => {
+                let idents = named.named.iter().map(|f| f.ident.as_ref().unwrap());
+                quote! { #ctor { #(#idents: #values,)* } }
+            }
+            Fields::Unnamed(_) => quote! { #ctor(#(#values,)*) },
+            Fields::Unit => ctor,
+        };
+
+        let literals = &self.literals;
+        let caps = if names.is_empty() {
+            quote! { _ }
+        } else {
+            quote! { caps }
+        };
+        // Full paths, so that local `Result` aliases and the like don't get in the way
+        Ok(quote! {
+            if let ::core::option::Option::Some(#caps) =
+                ::parse::match_pattern(s, &[#(#literals),*])
+            {
+                return ::core::result::Result::Ok(#body);
+            }
+        })
+    }
+}
+
+/// Generates `FromStr` from `#[pattern("...")]` format strings, with `{field}` placeholders for
+/// named fields and `{0}`, `{1}`... for tuple fields. Enums have one pattern per variant, tried
+/// in order.
+///
+/// Needs the `parse` crate, whose `PatternError` names the field that failed.
+#[proc_macro_derive(FromLine, attributes(pattern))]
+pub fn derive_from_line(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    from_line(&input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+fn from_line(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+
+    let mut patterns = vec![];
+    let mut matchers = vec![];
+    match &input.data {
+        Data::Struct(s) => {
+            let pattern = Pattern::from_attrs(&input.attrs, name)?;
+            matchers.push(pattern.matcher(quote! { Self }, &s.fields, name)?);
+            patterns.push(pattern.text);
+        }
+        Data::Enum(e) => {
+            for variant in e.variants.iter() {
+                let ident = &variant.ident;
+                let pattern = Pattern::from_attrs(&variant.attrs, ident)?;
+                matchers.push(pattern.matcher(quote! { Self::#ident }, &variant.fields, ident)?);
+                patterns.push(pattern.text);
+            }
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(name, "unions are not supported"));
+        }
+    }
+
+    Ok(quote! {
+        impl ::core::str::FromStr for #name {
+            type Err = ::parse::PatternError;
+
+            fn from_str(s: &str) -> ::core::result::Result<Self, Self::Err> {
+                #(#matchers)*
+                ::core::result::Result::Err(::parse::PatternError::mismatch(&[#(#patterns),*]))
+            }
+        }
+    })
+}
@@ -0,0 +1,25 @@
+//! The derived code has to compile wherever it lands, whatever that module calls `Result` or `Ok`.
+
+mod shadowed {
+    use grid_derive::FromLine;
+
+    // A one-parameter alias like the parse crate's, which the derived code must not pick up
+    #[allow(dead_code)]
+    type Result<T> = std::result::Result<T, String>;
+    #[allow(dead_code)]
+    struct Ok;
+
+    #[derive(FromLine, Debug, PartialEq, Eq)]
+    #[pattern("{a}-{b}")]
+    pub struct Pair {
+        pub a: u8,
+        pub b: u8,
+    }
+}
+
+#[test]
+fn test_shadowed_prelude() {
+    use shadowed::Pair;
+    assert_eq!(Pair { a: 1, b: 2 }, "1-2".parse().unwrap());
+    assert!("1+2".parse::<Pair>().is_err());
+}
@@ -0,0 +1,357 @@
+use std::fmt::Display;
+
+use crate::{Cell, Direction, Grid};
+
+const WORD: usize = u64::BITS as usize;
+
+/// A Life-like rule: which neighbour counts (0 to 8) turn a dead cell on, and which keep a live
+/// cell on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rule {
+    birth: u16,
+    survive: u16,
+}
+
+impl Rule {
+    /// Panics if a count is above 8, which no cell can have.
+    pub fn new(birth: &[u8], survive: &[u8]) -> Self {
+        Self::try_new(birth, survive).expect("A cell should have at most 8 neighbours")
+    }
+
+    /// Like [`Rule::new`], but `None` if a count is above 8.
+    pub fn try_new(birth: &[u8], survive: &[u8]) -> Option<Self> {
+        let mask = |counts: &[u8]| {
+            counts
+                .iter()
+                .try_fold(0u16, |acc, c| (*c <= 8).then(|| acc | (1 << c)))
+        };
+        Some(Self {
+            birth: mask(birth)?,
+            survive: mask(survive)?,
+        })
+    }
+
+    /// Conway's game of life, B3/S23.
+    pub fn life() -> Self {
+        Self::new(&[3], &[2, 3])
+    }
+}
+
+/// A grid of booleans packed 64 to a word, each row starting on a new word.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitGrid {
+    rows: usize,
+    cols: usize,
+    words_per_row: usize,
+    bits: Vec<u64>,
+}
+
+impl BitGrid {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        let words_per_row = cols.div_ceil(WORD);
+        Self {
+            rows,
+            cols,
+            words_per_row,
+            bits: vec![0; rows * words_per_row],
+        }
+    }
+
+    /// Packs a grid, keeping the cells for which `on` is true. Rows shorter than the widest one
+    /// are off past their end.
+    pub fn from_grid<T, F>(grid: &Grid<T>, on: F) -> Self
+    where
+        F: Fn(&T) -> bool,
+    {
+        let cols = grid.grid.iter().map(|r| r.len()).max().unwrap_or_default();
+        let mut bits = Self::new(grid.row_count(), cols);
+        for (row, cells) in grid.grid.iter().enumerate() {
+            for (col, val) in cells.iter().enumerate() {
+                if on(val) {
+                    bits.set(row, col, true);
+                }
+            }
+        }
+        bits
+    }
+
+    pub fn row_count(&self) -> usize {
+        self.rows
+    }
+
+    pub fn col_count(&self) -> usize {
+        self.cols
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Option<Cell<'static, bool>> {
+        if row >= self.rows || col >= self.cols {
+            return None;
+        }
+
+        let word = self.bits[row * self.words_per_row + col / WORD];
+        let val = if word >> (col % WORD) & 1 == 1 {
+            &true
+        } else {
+            &false
+        };
+        Some(Cell { row, col, val })
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, val: bool) {
+        if row >= self.rows || col >= self.cols {
+            panic!("Cell out of bounds");
+        }
+
+        let word = &mut self.bits[row * self.words_per_row + col / WORD];
+        if val {
+            *word |= 1 << (col % WORD);
+        } else {
+            *word &= !(1 << (col % WORD));
+        }
+    }
+
+    pub fn iter_cells(&self) -> impl Iterator<Item = Cell<'static, bool>> {
+        (0..self.rows).flat_map(move |row| (0..self.cols).filter_map(move |col| self.get(row, col)))
+    }
+
+    fn row_words(&self, row: usize) -> &[u64] {
+        &self.bits[row * self.words_per_row..(row + 1) * self.words_per_row]
+    }
+
+    /// Number of cells that are on.
+    pub fn count_ones(&self) -> usize {
+        self.bits.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Number of cells that are on in the `rows` by `cols` region starting at `(top, left)`,
+    /// clipped to the grid.
+    pub fn count_ones_in(&self, top: usize, left: usize, rows: usize, cols: usize) -> usize {
+        let right = left.saturating_add(cols).min(self.cols);
+        let bottom = top.saturating_add(rows).min(self.rows);
+        if left >= right {
+            return 0;
+        }
+
+        let mut count = 0;
+        for row in top..bottom {
+            let words = self.row_words(row);
+            for (index, word) in words
+                .iter()
+                .enumerate()
+                .take((right - 1) / WORD + 1)
+                .skip(left / WORD)
+            {
+                let start = (index * WORD).max(left) - index * WORD;
+                let end = ((index + 1) * WORD).min(right) - index * WORD;
+                let mask = (u64::MAX >> (WORD - (end - start))) << start;
+                count += (word & mask).count_ones() as usize;
+            }
+        }
+        count
+    }
+
+    fn neighbours_towards(
+        &self,
+        row: usize,
+        col: usize,
+        dirs: &[Direction],
+    ) -> Vec<(usize, usize)> {
+        dirs.iter()
+            .filter_map(|dir| {
+                let (dr, dc) = dir.delta();
+                let row = row.checked_add_signed(dr as isize)?;
+                let col = col.checked_add_signed(dc as isize)?;
+                (row < self.rows && col < self.cols).then_some((row, col))
+            })
+            .collect()
+    }
+
+    pub fn straight_neighbours(&self, row: usize, col: usize) -> Vec<(usize, usize)> {
+        self.neighbours_towards(row, col, &Direction::STRAIGHT)
+    }
+
+    pub fn diagonal_neighbours(&self, row: usize, col: usize) -> Vec<(usize, usize)> {
+        self.neighbours_towards(row, col, &Direction::DIAGONAL)
+    }
+
+    pub fn neighbours(&self, row: usize, col: usize) -> Vec<(usize, usize)> {
+        self.neighbours_towards(row, col, &Direction::ALL)
+    }
+
+    /// The row's words shifted so that each bit holds its western (`shift` = -1) or eastern
+    /// (`shift` = 1) neighbour, or the row itself for 0. Rows outside the grid are all off.
+    fn shifted(&self, row: Option<usize>, shift: i8) -> Vec<u64> {
+        let Some(row) = row.filter(|r| *r < self.rows) else {
+            return vec![0; self.words_per_row];
+        };
+
+        let words = self.row_words(row);
+        (0..self.words_per_row)
+            .map(|i| {
+                let word = words[i];
+                match shift {
+                    -1 => (word << 1) | i.checked_sub(1).map_or(0, |p| words[p] >> (WORD - 1)),
+                    1 => (word >> 1) | words.get(i + 1).map_or(0, |n| n << (WORD - 1)),
+                    _ => word,
+                }
+            })
+            .collect()
+    }
+
+    /// Applies a Life-like rule to every cell at once, 64 cells at a time.
+    ///
+    /// Neighbour counts are kept as four bit planes and summed with bitwise adders, so no cell
+    /// is ever looked at on its own.
+    pub fn step(&self, rule: Rule) -> Self {
+        let mut next = Self::new(self.rows, self.cols);
+        for row in 0..self.rows {
+            let planes = [
+                self.shifted(row.checked_sub(1), -1),
+                self.shifted(row.checked_sub(1), 0),
+                self.shifted(row.checked_sub(1), 1),
+                self.shifted(Some(row), -1),
+                self.shifted(Some(row), 1),
+                self.shifted(Some(row + 1), -1),
+                self.shifted(Some(row + 1), 0),
+                self.shifted(Some(row + 1), 1),
+            ];
+            let alive = self.row_words(row);
+
+            for i in 0..self.words_per_row {
+                // Bit-sliced counter: bit k of the count for each cell lives in counts[k]
+                let mut counts = [0u64; 4];
+                for plane in planes.iter() {
+                    let mut carry = plane[i];
+                    for bit in counts.iter_mut() {
+                        let next_carry = *bit & carry;
+                        *bit ^= carry;
+                        carry = next_carry;
+                    }
+                }
+
+                let mut born = 0;
+                let mut survives = 0;
+                for n in 0..=8u16 {
+                    let is_n = counts.iter().enumerate().fold(u64::MAX, |acc, (k, bit)| {
+                        acc & if n >> k & 1 == 1 { *bit } else { !*bit }
+                    });
+                    if rule.birth >> n & 1 == 1 {
+                        born |= is_n;
+                    }
+                    if rule.survive >> n & 1 == 1 {
+                        survives |= is_n;
+                    }
+                }
+
+                next.bits[row * self.words_per_row + i] =
+                    (alive[i] & survives) | (!alive[i] & born);
+            }
+            next.clear_padding(row);
+        }
+        next
+    }
+
+    /// Turns off the bits past the last column, which the shifts may have set.
+    fn clear_padding(&mut self, row: usize) {
+        let used = self.cols % WORD;
+        if used != 0 {
+            self.bits[(row + 1) * self.words_per_row - 1] &= u64::MAX >> (WORD - used);
+        }
+    }
+}
+
+impl Display for BitGrid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                let on = *self.get(row, col).unwrap().val;
+                write!(f, "{}", if on { '#' } else { '.' })?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BitGrid, Grid, Rule};
+
+    fn parse(s: &str) -> BitGrid {
+        let grid: Grid<char> = s.parse().unwrap();
+        BitGrid::from_grid(&grid, |c| *c == '#')
+    }
+
+    #[test]
+    fn test_accessors() {
+        let mut grid = BitGrid::new(2, 130);
+        grid.set(1, 129, true);
+        grid.set(0, 64, true);
+        grid.set(0, 63, true);
+        assert_eq!(Some(&true), grid.get(1, 129).map(|c| c.val));
+        assert_eq!(Some(&false), grid.get(1, 128).map(|c| c.val));
+        assert!(grid.get(2, 0).is_none());
+        assert_eq!(3, grid.count_ones());
+        assert_eq!(2, grid.count_ones_in(0, 63, 1, 2));
+        assert_eq!(1, grid.count_ones_in(0, 64, 2, 65));
+        assert_eq!(2, grid.count_ones_in(0, 64, 5, 500));
+        assert_eq!(2, grid.count_ones_in(0, 64, usize::MAX, usize::MAX));
+        assert_eq!(0, grid.count_ones_in(usize::MAX, usize::MAX, 1, 1));
+        assert_eq!(3, grid.iter_cells().filter(|c| *c.val).count());
+        assert_eq!(3, grid.neighbours(0, 0).len());
+    }
+
+    #[test]
+    fn test_life() {
+        let blinker = parse(".....\n..#..\n..#..\n..#..\n.....");
+        let next = blinker.step(Rule::life());
+        assert_eq!(".....\n.....\n.###.\n.....\n.....\n", next.to_string());
+        assert_eq!(blinker, next.step(Rule::life()));
+    }
+
+    #[test]
+    fn test_across_words() {
+        // A glider crossing the boundary between the first and second word of each row
+        let mut grid = BitGrid::new(6, 70);
+        for (row, col) in [(0, 63), (1, 64), (2, 62), (2, 63), (2, 64)] {
+            grid.set(row, col, true);
+        }
+
+        let mut moved = grid.clone();
+        for _ in 0..4 {
+            moved = moved.step(Rule::life());
+        }
+
+        let mut expected = BitGrid::new(6, 70);
+        for (row, col) in [(1, 64), (2, 65), (3, 63), (3, 64), (3, 65)] {
+            expected.set(row, col, true);
+        }
+        assert_eq!(expected, moved);
+    }
+
+    #[test]
+    fn test_removal_rule() {
+        // Rolls with fewer than four neighbouring rolls get removed
+        let rolls = parse("###\n###\n#..");
+        let next = rolls.step(Rule::new(&[], &[4, 5, 6, 7, 8]));
+        assert_eq!(".#.\n##.\n...\n", next.to_string());
+    }
+
+    #[test]
+    fn test_rule_counts() {
+        assert_eq!(
+            Some(Rule::new(&[], &[4, 5, 6, 7, 8])),
+            Rule::try_new(&[], &[4, 5, 6, 7, 8])
+        );
+        assert_eq!(None, Rule::try_new(&[9], &[]));
+        assert_eq!(None, Rule::try_new(&[3], &[2, 200]));
+    }
+
+    #[test]
+    fn test_ragged_source() {
+        let grid = parse("#\n..#\n.#");
+        assert_eq!(3, grid.col_count());
+        assert_eq!("#..\n..#\n.#.\n", grid.to_string());
+    }
+}
@@ -0,0 +1,93 @@
+use std::{collections::HashMap, hash::Hash};
+
+/// The states of an iterated simulation up to the first repeated one.
+///
+/// States `0..prefix` are only visited once, then states `prefix..prefix + period` repeat
+/// forever.
+#[derive(Debug, Clone)]
+pub struct Cycle<S> {
+    pub prefix: usize,
+    pub period: usize,
+    states: Vec<S>,
+}
+
+impl<S> Cycle<S> {
+    /// The state after `n` steps from the initial one, extrapolated through the cycle.
+    pub fn nth_state(&self, n: usize) -> &S {
+        if n < self.prefix {
+            return &self.states[n];
+        }
+
+        &self.states[self.prefix + (n - self.prefix) % self.period]
+    }
+}
+
+/// Applies `step` from `initial` until a state comes back.
+///
+/// This only returns once a state repeats, so the simulation must have finitely many states.
+pub fn find_cycle<S, F>(initial: S, mut step: F) -> Cycle<S>
+where
+    S: Hash + Eq + Clone,
+    F: FnMut(&S) -> S,
+{
+    let mut seen: HashMap<S, usize> = HashMap::new();
+    let mut state = initial;
+    let (prefix, period) = loop {
+        if let Some(first) = seen.get(&state) {
+            break (*first, seen.len() - first);
+        }
+
+        let next = step(&state);
+        seen.insert(state, seen.len());
+        state = next;
+    };
+
+    let mut states = seen.into_iter().collect::<Vec<_>>();
+    states.sort_by_key(|(_, index)| *index);
+
+    Cycle {
+        prefix,
+        period,
+        states: states.into_iter().map(|(state, _)| state).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::find_cycle;
+    use crate::{Direction, Grid, Topology};
+
+    #[test]
+    fn test_numbers() {
+        // 13 -> 9 -> 7 -> 1 -> 3 -> 9 -> ...
+        let cycle = find_cycle(13u64, |n| n * 3 % 10);
+        assert_eq!(1, cycle.prefix);
+        assert_eq!(4, cycle.period);
+        assert_eq!(&13, cycle.nth_state(0));
+        assert_eq!(&7, cycle.nth_state(2));
+        assert_eq!(&3, cycle.nth_state(1_000_000_000));
+    }
+
+    #[test]
+    fn test_grid_states() {
+        // A single marker walking east on a wrapping row
+        let mut initial = Grid::new().with_topology(Topology::WrapRows);
+        initial.add_row(vec![true, false, false]);
+
+        let cycle = find_cycle(initial, |grid| {
+            let marker = grid.iter_cells().find(|c| *c.val).unwrap();
+            let (row, col) = grid.step(marker.row, marker.col, Direction::East).unwrap();
+            let mut next = grid.clone();
+            next.set(marker.row, marker.col, false);
+            next.set(row, col, true);
+            next
+        });
+
+        assert_eq!(0, cycle.prefix);
+        assert_eq!(3, cycle.period);
+        assert_eq!(
+            Some(&true),
+            cycle.nth_state(1_000_000_000).get(0, 1).map(|c| c.val)
+        );
+    }
+}
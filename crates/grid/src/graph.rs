@@ -0,0 +1,289 @@
+use std::collections::VecDeque;
+
+use crate::{Cell, Grid};
+
+pub type NodeId = usize;
+
+/// A directed graph stored as adjacency lists, with data on both nodes and edges.
+#[derive(Debug, Clone)]
+pub struct Graph<N, E> {
+    nodes: Vec<N>,
+    edges: Vec<Vec<(NodeId, E)>>,
+}
+
+impl<N, E> Graph<N, E> {
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+        }
+    }
+
+    pub fn add_node(&mut self, node: N) -> NodeId {
+        self.nodes.push(node);
+        self.edges.push(Vec::new());
+        self.nodes.len() - 1
+    }
+
+    pub fn add_edge(&mut self, from: NodeId, to: NodeId, edge: E) {
+        self.edges[from].push((to, edge));
+    }
+
+    pub fn node(&self, id: NodeId) -> &N {
+        &self.nodes[id]
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges.iter().map(|e| e.len()).sum()
+    }
+
+    pub fn neighbours(&self, id: NodeId) -> impl Iterator<Item = (NodeId, &E)> {
+        self.edges[id].iter().map(|(to, edge)| (*to, edge))
+    }
+
+    /// Nodes reachable from `start` with their distance in edges, closest first.
+    pub fn bfs(&self, start: NodeId) -> Vec<(NodeId, usize)> {
+        let mut seen = vec![false; self.len()];
+        let mut queue = VecDeque::from([(start, 0)]);
+        seen[start] = true;
+
+        let mut res = vec![];
+        while let Some((id, depth)) = queue.pop_front() {
+            res.push((id, depth));
+            for (next, _) in self.neighbours(id) {
+                if !seen[next] {
+                    seen[next] = true;
+                    queue.push_back((next, depth + 1));
+                }
+            }
+        }
+        res
+    }
+
+    /// Nodes reachable from `start`, in depth-first pre-order.
+    pub fn dfs(&self, start: NodeId) -> Vec<NodeId> {
+        let mut seen = vec![false; self.len()];
+        let mut stack = vec![start];
+
+        let mut res = vec![];
+        while let Some(id) = stack.pop() {
+            if seen[id] {
+                continue;
+            }
+            seen[id] = true;
+            res.push(id);
+            // Pushed in reverse so that the first edge is explored first
+            stack.extend(self.edges[id].iter().rev().map(|(to, _)| *to));
+        }
+        res
+    }
+
+    /// Orders the nodes so that every edge goes forwards, or `None` if there is a cycle.
+    pub fn topological_sort(&self) -> Option<Vec<NodeId>> {
+        let mut incoming = vec![0; self.len()];
+        for edges in self.edges.iter() {
+            for (to, _) in edges {
+                incoming[*to] += 1;
+            }
+        }
+
+        let mut queue = (0..self.len())
+            .filter(|id| incoming[*id] == 0)
+            .collect::<VecDeque<_>>();
+        let mut res = Vec::with_capacity(self.len());
+        while let Some(id) = queue.pop_front() {
+            res.push(id);
+            for (next, _) in self.neighbours(id) {
+                incoming[next] -= 1;
+                if incoming[next] == 0 {
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        (res.len() == self.len()).then_some(res)
+    }
+
+    /// Tarjan's algorithm, run without recursion so that long chains can't overflow the stack.
+    ///
+    /// Components come out in reverse topological order: no edge goes from a component to one
+    /// listed before it.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<NodeId>> {
+        let mut index = vec![usize::MAX; self.len()];
+        let mut low = vec![0; self.len()];
+        let mut on_stack = vec![false; self.len()];
+        let mut stack = vec![];
+        let mut next_index = 0;
+        let mut res = vec![];
+
+        for root in 0..self.len() {
+            if index[root] != usize::MAX {
+                continue;
+            }
+
+            // Each frame is a node and how many of its edges were already followed
+            let mut frames = vec![(root, 0)];
+            index[root] = next_index;
+            low[root] = next_index;
+            next_index += 1;
+            stack.push(root);
+            on_stack[root] = true;
+
+            while let Some((id, edge)) = frames.last_mut() {
+                let id = *id;
+                if let Some((next, _)) = self.edges[id].get(*edge) {
+                    *edge += 1;
+                    let next = *next;
+                    if index[next] == usize::MAX {
+                        index[next] = next_index;
+                        low[next] = next_index;
+                        next_index += 1;
+                        stack.push(next);
+                        on_stack[next] = true;
+                        frames.push((next, 0));
+                    } else if on_stack[next] {
+                        low[id] = low[id].min(index[next]);
+                    }
+                    continue;
+                }
+
+                frames.pop();
+                if let Some((parent, _)) = frames.last() {
+                    low[*parent] = low[*parent].min(low[id]);
+                }
+
+                if low[id] == index[id] {
+                    let mut component = vec![];
+                    while let Some(member) = stack.pop() {
+                        on_stack[member] = false;
+                        component.push(member);
+                        if member == id {
+                            break;
+                        }
+                    }
+                    res.push(component);
+                }
+            }
+        }
+
+        res
+    }
+
+    /// Number of distinct paths from `from` to any node for which `target` is true, memoised per
+    /// node. Returns `None` if the graph has a cycle.
+    pub fn count_paths<F>(&self, from: NodeId, target: F) -> Option<u128>
+    where
+        F: Fn(NodeId) -> bool,
+    {
+        let order = self.topological_sort()?;
+        let mut paths = vec![0u128; self.len()];
+        for id in order.into_iter().rev() {
+            paths[id] = target(id) as u128
+                + self
+                    .neighbours(id)
+                    .map(|(next, _)| paths[next])
+                    .sum::<u128>();
+        }
+        Some(paths[from])
+    }
+}
+
+impl<N, E> Default for Graph<N, E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Grid<T> {
+    /// Builds a graph with one node per cell, numbered row by row, and an edge for every move
+    /// that `moves` allows out of a cell.
+    pub fn to_graph<E, F>(&self, moves: F) -> Graph<(usize, usize), E>
+    where
+        F: Fn(&Grid<T>, Cell<'_, T>) -> Vec<((usize, usize), E)>,
+    {
+        let cols = self.col_count();
+        let mut graph = Graph::new();
+        for cell in self.iter_cells() {
+            graph.add_node((cell.row, cell.col));
+        }
+
+        for cell in self.iter_cells() {
+            let from = cell.row * cols + cell.col;
+            for ((row, col), edge) in moves(self, cell) {
+                if row < self.row_count() && col < cols {
+                    graph.add_edge(from, row * cols + col, edge);
+                }
+            }
+        }
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Direction, Graph, Grid};
+
+    fn diamond() -> Graph<char, ()> {
+        // a -> b -> d, a -> c -> d, d -> e
+        let mut graph = Graph::new();
+        let ids = "abcde"
+            .chars()
+            .map(|c| graph.add_node(c))
+            .collect::<Vec<_>>();
+        for (from, to) in [(0, 1), (0, 2), (1, 3), (2, 3), (3, 4)] {
+            graph.add_edge(ids[from], ids[to], ());
+        }
+        graph
+    }
+
+    #[test]
+    fn test_traversals() {
+        let graph = diamond();
+        assert_eq!(vec![(0, 0), (1, 1), (2, 1), (3, 2), (4, 3)], graph.bfs(0));
+        assert_eq!(vec![0, 1, 3, 4, 2], graph.dfs(0));
+        assert_eq!(Some(vec![0, 1, 2, 3, 4]), graph.topological_sort());
+        assert_eq!(Some(2), graph.count_paths(0, |id| id == 4));
+        // Every path to e goes through d, so both get counted
+        assert_eq!(Some(4), graph.count_paths(0, |id| id >= 3));
+    }
+
+    #[test]
+    fn test_cycles() {
+        let mut graph = diamond();
+        graph.add_edge(4, 1, ());
+        assert_eq!(None, graph.topological_sort());
+        assert_eq!(None, graph.count_paths(0, |id| id == 4));
+
+        let mut components = graph.strongly_connected_components();
+        for component in components.iter_mut() {
+            component.sort();
+        }
+        assert_eq!(vec![vec![1, 3, 4], vec![2], vec![0]], components);
+    }
+
+    #[test]
+    fn test_from_grid() {
+        let grid: Grid<char> = "..#\n...\n#..".parse().unwrap();
+        let graph = grid.to_graph(|grid, cell| {
+            [Direction::East, Direction::South]
+                .iter()
+                .filter_map(|dir| grid.step(cell.row, cell.col, *dir))
+                .filter(|(r, c)| grid.get(*r, *c).is_some_and(|c| *c.val == '.'))
+                .map(|pos| (pos, ()))
+                .collect()
+        });
+
+        assert_eq!(9, graph.len());
+        assert_eq!(&(2, 2), graph.node(8));
+        // Monotone paths from the top left to the bottom right avoiding the walls
+        assert_eq!(Some(4), graph.count_paths(0, |id| id == 8));
+    }
+}
@@ -0,0 +1,359 @@
+use std::{collections::VecDeque, fmt::Debug, str::FromStr};
+
+use crate::{Grid, ParseGridError};
+
+/// Which cells count as neighbours in a [`Grid3`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connectivity {
+    /// The 6 cells sharing a face.
+    Faces,
+    /// The 18 cells sharing a face or an edge.
+    Edges,
+    /// The 26 cells sharing a face, an edge or a corner.
+    Corners,
+}
+
+impl Connectivity {
+    fn includes(&self, delta: (i64, i64, i64)) -> bool {
+        let moved = [delta.0, delta.1, delta.2]
+            .iter()
+            .filter(|d| **d != 0)
+            .count();
+        match self {
+            Self::Faces => moved == 1,
+            Self::Edges => (1..=2).contains(&moved),
+            Self::Corners => moved >= 1,
+        }
+    }
+}
+
+pub type Point3 = (usize, usize, usize);
+
+fn volume(size: Point3) -> Option<usize> {
+    size.0.checked_mul(size.1)?.checked_mul(size.2)
+}
+
+/// A dense three-dimensional grid, indexed by `(x, y, z)`.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Grid3<T> {
+    size: Point3,
+    cells: Vec<T>,
+    origin: (i64, i64, i64),
+}
+
+impl<T> Grid3<T>
+where
+    T: Clone,
+{
+    pub fn new(size: Point3, val: T) -> Self {
+        Self::try_new(size, val).expect("A Grid3 should have fewer than usize::MAX cells")
+    }
+
+    /// Like [`Grid3::new`], but `None` if the number of cells doesn't fit in a `usize`.
+    pub fn try_new(size: Point3, val: T) -> Option<Self> {
+        Some(Self {
+            size,
+            cells: vec![val; volume(size)?],
+            origin: (0, 0, 0),
+        })
+    }
+
+    /// A copy of the `z` layer, with `y` as rows and `x` as columns.
+    pub fn slice_z(&self, z: usize) -> Grid<T> {
+        let mut grid = Grid::with_capacity(self.size.1);
+        for y in 0..self.size.1 {
+            grid.add_row(
+                (0..self.size.0)
+                    .filter_map(|x| self.get(x, y, z).cloned())
+                    .collect(),
+            );
+        }
+        grid
+    }
+}
+
+impl<T> Grid3<T> {
+    pub fn size(&self) -> Point3 {
+        self.size
+    }
+
+    /// The input coordinates of the cell at `(0, 0, 0)`, for grids built from points.
+    pub fn origin(&self) -> (i64, i64, i64) {
+        self.origin
+    }
+
+    fn index(&self, x: usize, y: usize, z: usize) -> Option<usize> {
+        (x < self.size.0 && y < self.size.1 && z < self.size.2)
+            .then(|| (z * self.size.1 + y) * self.size.0 + x)
+    }
+
+    pub fn get(&self, x: usize, y: usize, z: usize) -> Option<&T> {
+        self.index(x, y, z).map(|i| &self.cells[i])
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, z: usize, val: T) {
+        let Some(i) = self.index(x, y, z) else {
+            panic!("Cell out of bounds");
+        };
+        self.cells[i] = val;
+    }
+
+    pub fn iter_cells(&self) -> impl Iterator<Item = (Point3, &T)> {
+        let (sx, sy, _) = self.size;
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(i, val)| ((i % sx, (i / sx) % sy, i / (sx * sy)), val))
+    }
+
+    pub fn neighbours(&self, x: usize, y: usize, z: usize, conn: Connectivity) -> Vec<Point3> {
+        let mut res = Vec::with_capacity(26);
+        for dz in -1..=1 {
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    if !conn.includes((dx, dy, dz)) {
+                        continue;
+                    }
+
+                    let (Some(nx), Some(ny), Some(nz)) = (
+                        x.checked_add_signed(dx as isize),
+                        y.checked_add_signed(dy as isize),
+                        z.checked_add_signed(dz as isize),
+                    ) else {
+                        continue;
+                    };
+
+                    if self.index(nx, ny, nz).is_some() {
+                        res.push((nx, ny, nz));
+                    }
+                }
+            }
+        }
+        res
+    }
+
+    /// Every cell reachable from `start` through cells for which `passable` is true.
+    pub fn flood_fill<F>(&self, start: Point3, conn: Connectivity, passable: F) -> Grid3<bool>
+    where
+        F: Fn(&T) -> bool,
+    {
+        self.flood_fill_from([start], conn, passable)
+    }
+
+    /// Like [`Grid3::flood_fill`], spreading from several cells at once.
+    fn flood_fill_from<I, F>(&self, starts: I, conn: Connectivity, passable: F) -> Grid3<bool>
+    where
+        I: IntoIterator<Item = Point3>,
+        F: Fn(&T) -> bool,
+    {
+        let mut seen = Grid3 {
+            size: self.size,
+            cells: vec![false; self.cells.len()],
+            origin: self.origin,
+        };
+
+        let mut queue = VecDeque::new();
+        for (x, y, z) in starts {
+            if !seen.get(x, y, z).unwrap_or(&true) && self.get(x, y, z).is_some_and(&passable) {
+                seen.set(x, y, z, true);
+                queue.push_back((x, y, z));
+            }
+        }
+
+        while let Some((x, y, z)) = queue.pop_front() {
+            for (nx, ny, nz) in self.neighbours(x, y, z, conn) {
+                if !seen.get(nx, ny, nz).unwrap() && passable(self.get(nx, ny, nz).unwrap()) {
+                    seen.set(nx, ny, nz, true);
+                    queue.push_back((nx, ny, nz));
+                }
+            }
+        }
+
+        seen
+    }
+}
+
+impl Grid3<bool> {
+    /// Largest grid that parsing will build, in cells.
+    pub const MAX_CELLS: usize = 1 << 26;
+
+    /// The origin and size of the grid holding `points` with a layer of cells around them, if
+    /// the coordinates don't overflow.
+    fn extent(points: &[(i64, i64, i64)]) -> Option<((i64, i64, i64), Point3)> {
+        let axis = |f: fn(&(i64, i64, i64)) -> i64| -> Option<(i64, usize)> {
+            let min = points.iter().map(f).min().unwrap_or(0);
+            let max = points.iter().map(f).max().unwrap_or(0);
+            let origin = min.checked_sub(1)?;
+            let len = max.checked_sub(origin)?.checked_add(2)?;
+            Some((origin, usize::try_from(len).ok()?))
+        };
+        let (x, w) = axis(|p| p.0)?;
+        let (y, h) = axis(|p| p.1)?;
+        let (z, d) = axis(|p| p.2)?;
+        Some(((x, y, z), (w, h, d)))
+    }
+
+    /// Builds a grid with the given points set, shifted so that there is an empty layer of
+    /// cells all around them.
+    pub fn from_points(points: &[(i64, i64, i64)]) -> Self {
+        let (origin, size) = Self::extent(points).expect("The points should fit in a Grid3");
+        let mut grid = Self::new(size, false);
+        grid.origin = origin;
+        for (x, y, z) in points {
+            grid.set(
+                (x - origin.0) as usize,
+                (y - origin.1) as usize,
+                (z - origin.2) as usize,
+                true,
+            );
+        }
+        grid
+    }
+
+    fn faces_where<F>(&self, open: F) -> usize
+    where
+        F: Fn(Option<Point3>) -> bool,
+    {
+        let mut faces = 0;
+        for ((x, y, z), filled) in self.iter_cells() {
+            if !filled {
+                continue;
+            }
+
+            let neighbours = self.neighbours(x, y, z, Connectivity::Faces);
+            // Faces on the edge of the grid have no neighbour
+            faces += (6 - neighbours.len()) * open(None) as usize;
+            faces += neighbours.into_iter().filter(|n| open(Some(*n))).count();
+        }
+        faces
+    }
+
+    /// Number of faces of set cells that don't touch another set cell.
+    pub fn surface_area(&self) -> usize {
+        self.faces_where(|n| n.is_none_or(|(x, y, z)| !self.get(x, y, z).unwrap()))
+    }
+
+    /// Number of faces of set cells reachable from outside, leaving out enclosed pockets.
+    ///
+    /// The outside is whatever empty cells connect to the edge of the grid, so a set cell on the
+    /// edge doesn't hide the rest.
+    pub fn exterior_surface_area(&self) -> usize {
+        let (sx, sy, sz) = self.size;
+        let edge = self.iter_cells().map(|(p, _)| p).filter(|&(x, y, z)| {
+            x == 0 || y == 0 || z == 0 || x + 1 == sx || y + 1 == sy || z + 1 == sz
+        });
+        let outside = self.flood_fill_from(edge, Connectivity::Faces, |filled| !filled);
+        self.faces_where(|n| n.is_none_or(|(x, y, z)| *outside.get(x, y, z).unwrap()))
+    }
+}
+
+/// Parses one `x,y,z` point per line into a [`Grid3::from_points`] grid, failing rather than
+/// allocating more than [`Grid3::MAX_CELLS`] cells.
+impl FromStr for Grid3<bool> {
+    type Err = ParseGridError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let points = s
+            .lines()
+            .map(|line| {
+                let coords = line
+                    .split(',')
+                    .map(|n| n.trim().parse::<i64>().map_err(|_| ParseGridError))
+                    .collect::<Result<Vec<_>, _>>()?;
+                match coords.as_slice() {
+                    [x, y, z] => Ok((*x, *y, *z)),
+                    _ => Err(ParseGridError),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let (_, size) = Self::extent(&points).ok_or(ParseGridError)?;
+        if volume(size).is_none_or(|cells| cells > Self::MAX_CELLS) {
+            return Err(ParseGridError);
+        }
+        Ok(Self::from_points(&points))
+    }
+}
+
+impl<T> Debug for Grid3<T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Grid3")
+            .field("size", &self.size)
+            .field("origin", &self.origin)
+            .field("cells", &self.cells)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Connectivity, Grid3};
+
+    #[test]
+    fn test_too_large() {
+        assert!(Grid3::try_new((usize::MAX, 2, 1), 0u8).is_none());
+
+        // Two of day 08's boxes would span trillions of cells
+        assert!("66808,15875,57273\n6,2,6".parse::<Grid3<bool>>().is_err());
+        let extremes = format!("{},0,0\n{},0,0", i64::MAX, i64::MIN);
+        assert!(extremes.parse::<Grid3<bool>>().is_err());
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = Grid3::new((3, 3, 3), 0u8);
+        assert_eq!(6, grid.neighbours(1, 1, 1, Connectivity::Faces).len());
+        assert_eq!(18, grid.neighbours(1, 1, 1, Connectivity::Edges).len());
+        assert_eq!(26, grid.neighbours(1, 1, 1, Connectivity::Corners).len());
+        assert_eq!(7, grid.neighbours(0, 0, 0, Connectivity::Corners).len());
+    }
+
+    #[test]
+    fn test_slice() {
+        let grid: Grid3<bool> = "1,1,1\n2,1,1\n2,2,2".parse().unwrap();
+        assert_eq!((4, 4, 4), grid.size());
+        assert_eq!((0, 0, 0), grid.origin());
+        let layer = grid
+            .slice_z(1)
+            .to_string()
+            .replace("false", ".")
+            .replace("true", "#");
+        assert_eq!("....\n.##.\n....\n....\n", layer);
+    }
+
+    #[test]
+    fn test_surface() {
+        let grid: Grid3<bool> = "1,1,1\n2,1,1".parse().unwrap();
+        assert_eq!(10, grid.surface_area());
+        assert_eq!(10, grid.exterior_surface_area());
+
+        // A 3x3x3 cube with its centre missing hides 6 faces inside
+        let mut points = vec![];
+        for x in 0..3 {
+            for y in 0..3 {
+                for z in 0..3 {
+                    if (x, y, z) != (1, 1, 1) {
+                        points.push((x, y, z));
+                    }
+                }
+            }
+        }
+        let grid = Grid3::from_points(&points);
+        assert_eq!(54 + 6, grid.surface_area());
+        assert_eq!(54, grid.exterior_surface_area());
+
+        // Without the layer of empty cells `from_points` adds, the outside starts all along
+        // the edge rather than in the corner
+        let mut grid = Grid3::new((3, 3, 3), false);
+        grid.set(0, 0, 0, true);
+        grid.set(2, 2, 2, true);
+        assert_eq!(12, grid.exterior_surface_area());
+
+        let mut grid = Grid3::new((3, 3, 3), true);
+        grid.set(1, 1, 1, false);
+        assert_eq!(54, grid.exterior_surface_area());
+    }
+}
@@ -0,0 +1,282 @@
+use std::{collections::HashMap, fmt::Display, str::FromStr};
+
+use crate::ParseGridError;
+
+/// The six neighbours of a pointy-topped hexagon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HexDirection {
+    East,
+    NorthEast,
+    NorthWest,
+    West,
+    SouthWest,
+    SouthEast,
+}
+
+impl HexDirection {
+    pub const ALL: [HexDirection; 6] = [
+        Self::East,
+        Self::NorthEast,
+        Self::NorthWest,
+        Self::West,
+        Self::SouthWest,
+        Self::SouthEast,
+    ];
+
+    /// The `(q, r)` axial offset of a single step.
+    pub fn delta(&self) -> (i64, i64) {
+        match self {
+            Self::East => (1, 0),
+            Self::NorthEast => (1, -1),
+            Self::NorthWest => (0, -1),
+            Self::West => (-1, 0),
+            Self::SouthWest => (-1, 1),
+            Self::SouthEast => (0, 1),
+        }
+    }
+}
+
+/// A hexagon in axial coordinates: `q` grows eastwards and `r` south-eastwards.
+///
+/// The third cube coordinate is `s = -q - r`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Hex {
+    pub q: i64,
+    pub r: i64,
+}
+
+impl Hex {
+    pub fn new(q: i64, r: i64) -> Self {
+        Self { q, r }
+    }
+
+    pub fn s(&self) -> i64 {
+        -self.q - self.r
+    }
+
+    pub fn step(&self, dir: HexDirection) -> Self {
+        self.step_by(dir, 1)
+    }
+
+    pub fn step_by(&self, dir: HexDirection, n: i64) -> Self {
+        let (dq, dr) = dir.delta();
+        Self::new(self.q + dq * n, self.r + dr * n)
+    }
+
+    pub fn neighbours(&self) -> Vec<Hex> {
+        HexDirection::ALL
+            .iter()
+            .map(|dir| self.step(*dir))
+            .collect()
+    }
+
+    /// Number of steps between two hexagons.
+    pub fn distance(&self, other: &Self) -> u64 {
+        let dq = self.q.abs_diff(other.q);
+        let dr = self.r.abs_diff(other.r);
+        let ds = self.s().abs_diff(other.s());
+        dq.max(dr).max(ds)
+    }
+
+    /// The hexagons exactly `radius` steps away, going anticlockwise from the south-west.
+    pub fn ring(&self, radius: u32) -> Vec<Hex> {
+        if radius == 0 {
+            return vec![*self];
+        }
+
+        let radius = radius as i64;
+        let mut res = Vec::with_capacity(6 * radius as usize);
+        let mut hex = self.step_by(HexDirection::SouthWest, radius);
+        for dir in HexDirection::ALL {
+            for _ in 0..radius {
+                res.push(hex);
+                hex = hex.step(dir);
+            }
+        }
+        res
+    }
+
+    /// The hexagons up to `radius` steps away, from the centre outwards one ring at a time.
+    pub fn spiral(&self, radius: u32) -> Vec<Hex> {
+        (0..=radius).flat_map(|r| self.ring(r)).collect()
+    }
+
+    /// The hexagon at a row and column of the "odd-r" layout, where odd rows are shifted right
+    /// by half a cell.
+    pub fn from_offset(row: i64, col: i64) -> Self {
+        Self::new(col - (row - (row & 1)) / 2, row)
+    }
+
+    pub fn to_offset(&self) -> (i64, i64) {
+        (self.r, self.q + (self.r - (self.r & 1)) / 2)
+    }
+}
+
+/// Cells on a hexagonal lattice, stored by their axial coordinates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HexGrid<T> {
+    cells: HashMap<Hex, T>,
+}
+
+impl<T> HexGrid<T> {
+    pub fn new() -> Self {
+        Self {
+            cells: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, hex: Hex) -> Option<&T> {
+        self.cells.get(&hex)
+    }
+
+    pub fn set(&mut self, hex: Hex, val: T) {
+        self.cells.insert(hex, val);
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Hex, &T)> {
+        self.cells.iter().map(|(hex, val)| (*hex, val))
+    }
+
+    /// The neighbours of `hex` that are part of the grid.
+    pub fn neighbours(&self, hex: Hex) -> Vec<Hex> {
+        hex.neighbours()
+            .into_iter()
+            .filter(|n| self.cells.contains_key(n))
+            .collect()
+    }
+}
+
+impl<T> Default for HexGrid<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parses the usual text layout of a hex map, where cells are separated by a space and every
+/// other row is indented by one character:
+///
+/// ```text
+/// a b c
+///  d e f
+/// g h i
+/// ```
+///
+/// The first cell of the first line is at `(0, 0)`, each line is one step south-east of the
+/// previous one.
+impl<T> FromStr for HexGrid<T>
+where
+    T: TryFrom<char>,
+{
+    type Err = ParseGridError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut grid = Self::new();
+        let mut origin = None;
+        for (r, line) in s.lines().enumerate() {
+            let r = r as i64;
+            for (x, ch) in line.chars().enumerate().filter(|(_, ch)| *ch != ' ') {
+                // In the text, moving east is two characters and moving south-east is one
+                let doubled = x as i64 - r;
+                let origin = *origin.get_or_insert(doubled);
+                if (doubled - origin) % 2 != 0 {
+                    return Err(ParseGridError);
+                }
+
+                let val = ch.try_into().map_err(|_| ParseGridError)?;
+                grid.set(Hex::new((doubled - origin).div_euclid(2), r), val);
+            }
+        }
+
+        Ok(grid)
+    }
+}
+
+impl<T> Display for HexGrid<T>
+where
+    T: Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Some(min_r) = self.cells.keys().map(|h| h.r).min() else {
+            return Ok(());
+        };
+        let max_r = self.cells.keys().map(|h| h.r).max().unwrap();
+        let min_x = self.cells.keys().map(|h| 2 * h.q + h.r).min().unwrap();
+
+        for r in min_r..=max_r {
+            let mut row = self.iter().filter(|(h, _)| h.r == r).collect::<Vec<_>>();
+            row.sort_by_key(|(h, _)| h.q);
+
+            let mut x = min_x;
+            for (hex, val) in row {
+                let target = 2 * hex.q + hex.r;
+                write!(f, "{:width$}{val}", "", width = (target - x) as usize)?;
+                x = target + 1;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Hex, HexDirection, HexGrid};
+
+    #[test]
+    fn test_distance() {
+        let origin = Hex::new(0, 0);
+        assert_eq!(0, origin.distance(&origin));
+        assert_eq!(3, origin.distance(&Hex::new(3, -3)));
+        assert_eq!(
+            4,
+            origin.distance(
+                &origin
+                    .step_by(HexDirection::SouthWest, 2)
+                    .step_by(HexDirection::West, 2)
+            )
+        );
+        assert!(origin.neighbours().iter().all(|n| origin.distance(n) == 1));
+    }
+
+    #[test]
+    fn test_rings() {
+        let centre = Hex::new(2, -1);
+        assert_eq!(vec![centre], centre.ring(0));
+        assert_eq!(12, centre.ring(2).len());
+        assert!(centre.ring(2).iter().all(|h| centre.distance(h) == 2));
+        assert_eq!(1 + 6 + 12 + 18, centre.spiral(3).len());
+    }
+
+    #[test]
+    fn test_offset() {
+        for row in -3..3 {
+            for col in -3..3 {
+                assert_eq!((row, col), Hex::from_offset(row, col).to_offset());
+            }
+        }
+        assert_eq!(Hex::new(0, 1), Hex::from_offset(1, 0));
+    }
+
+    #[test]
+    fn test_parse() {
+        let text = "a b c\n d e f\ng h i\n";
+        let grid: HexGrid<char> = text.parse().unwrap();
+        assert_eq!(9, grid.len());
+        assert_eq!(Some(&'e'), grid.get(Hex::new(1, 1)));
+        // Third row goes back one to the left: g sits south-west of d
+        assert_eq!(Some(&'g'), grid.get(Hex::new(-1, 2)));
+        assert_eq!(6, grid.neighbours(Hex::new(1, 1)).len());
+        assert_eq!(text, grid.to_string());
+
+        assert!("a b\nc d".parse::<HexGrid<char>>().is_err());
+    }
+}
@@ -0,0 +1,202 @@
+use std::io::{self, Write};
+
+use crate::Grid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb(0, 0, 0);
+    pub const WHITE: Rgb = Rgb(255, 255, 255);
+}
+
+/// A raster image, written out without any dependency as either PPM or PNG.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![Rgb::BLACK; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<Rgb> {
+        if x >= self.width {
+            return None;
+        }
+        self.pixels.get(y * self.width + x).copied()
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, colour: Rgb) {
+        if x >= self.width || y >= self.height {
+            panic!("Pixel out of bounds");
+        }
+        self.pixels[y * self.width + x] = colour;
+    }
+
+    /// Binary PPM (P6), which most image viewers open directly.
+    pub fn write_ppm<W: Write>(&self, mut w: W) -> io::Result<()> {
+        write!(w, "P6\n{} {}\n255\n", self.width, self.height)?;
+        for px in self.pixels.iter() {
+            w.write_all(&[px.0, px.1, px.2])?;
+        }
+        Ok(())
+    }
+
+    /// 8-bit RGB PNG. The pixel data is stored uncompressed, which keeps the encoder tiny.
+    pub fn write_png<W: Write>(&self, mut w: W) -> io::Result<()> {
+        w.write_all(b"\x89PNG\r\n\x1a\n")?;
+
+        let mut header = Vec::with_capacity(13);
+        header.extend((self.width as u32).to_be_bytes());
+        header.extend((self.height as u32).to_be_bytes());
+        // Bit depth 8, truecolour, default compression, filter and no interlacing
+        header.extend([8, 2, 0, 0, 0]);
+        write_chunk(&mut w, b"IHDR", &header)?;
+
+        let mut raw = Vec::with_capacity(self.height * (self.width * 3 + 1));
+        for row in self.pixels.chunks(self.width.max(1)) {
+            // Each scanline starts with its filter type, 0 being none
+            raw.push(0);
+            for px in row {
+                raw.extend([px.0, px.1, px.2]);
+            }
+        }
+        write_chunk(&mut w, b"IDAT", &zlib_stored(&raw))?;
+        write_chunk(&mut w, b"IEND", &[])
+    }
+}
+
+impl<T> Grid<T> {
+    /// Draws every cell as a `scale` by `scale` square of the colour picked by `colour`.
+    pub fn to_image<F>(&self, scale: usize, colour: F) -> Image
+    where
+        F: Fn(&T) -> Rgb,
+    {
+        let mut image = Image::new(self.col_count() * scale, self.row_count() * scale);
+        for cell in self.iter_cells() {
+            let px = colour(cell.val);
+            for y in 0..scale {
+                for x in 0..scale {
+                    image.set(cell.col * scale + x, cell.row * scale + y, px);
+                }
+            }
+        }
+        image
+    }
+}
+
+fn write_chunk<W: Write>(w: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    w.write_all(&(data.len() as u32).to_be_bytes())?;
+    w.write_all(kind)?;
+    w.write_all(data)?;
+    let crc = crc32(kind.iter().chain(data.iter()).copied());
+    w.write_all(&crc.to_be_bytes())
+}
+
+/// Wraps `data` in a zlib stream made only of stored (uncompressed) deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = u16::MAX as usize;
+
+    let mut out = Vec::with_capacity(data.len() + data.len() / MAX_BLOCK * 5 + 11);
+    out.extend([0x78, 0x01]);
+
+    let mut blocks = data.chunks(MAX_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        out.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        out.push(last as u8);
+        out.extend(len.to_le_bytes());
+        out.extend((!len).to_le_bytes());
+        out.extend(block);
+    }
+
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32<I>(bytes: I) -> u32
+where
+    I: IntoIterator<Item = u8>,
+{
+    let mut table = [0u32; 256];
+    for (n, entry) in table.iter_mut().enumerate() {
+        let mut c = n as u32;
+        for _ in 0..8 {
+            c = if c & 1 == 1 {
+                0xedb88320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+        }
+        *entry = c;
+    }
+
+    !bytes.into_iter().fold(!0u32, |crc, byte| {
+        table[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), byte| {
+        let a = (a + *byte as u32) % MOD;
+        (a, (b + a) % MOD)
+    });
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{adler32, crc32};
+    use crate::{Grid, Rgb};
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(0xae426082, crc32(*b"IEND"));
+        assert_eq!(0x11e60398, adler32(b"Wikipedia"));
+    }
+
+    #[test]
+    fn test_ppm() {
+        let grid: Grid<char> = "#.\n.#".parse().unwrap();
+        let image = grid.to_image(1, |c| if *c == '#' { Rgb::WHITE } else { Rgb::BLACK });
+        let mut out = vec![];
+        image.write_ppm(&mut out).unwrap();
+        assert_eq!(b"P6\n2 2\n255\n", &out[..11]);
+        assert_eq!(&[255, 255, 255, 0, 0, 0], &out[11..17]);
+    }
+
+    #[test]
+    fn test_png() {
+        let grid: Grid<char> = "#.\n.#".parse().unwrap();
+        let image = grid.to_image(3, |c| if *c == '#' { Rgb::WHITE } else { Rgb::BLACK });
+        assert_eq!(6, image.width());
+        assert_eq!(Some(Rgb::WHITE), image.get(2, 2));
+        assert_eq!(Some(Rgb::BLACK), image.get(3, 2));
+
+        let mut out = vec![];
+        image.write_png(&mut out).unwrap();
+        assert_eq!(b"\x89PNG\r\n\x1a\n", &out[..8]);
+        assert_eq!(b"IHDR", &out[12..16]);
+        assert_eq!(b"IEND", &out[out.len() - 8..out.len() - 4]);
+    }
+}
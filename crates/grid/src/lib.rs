@@ -1,11 +1,43 @@
 use std::{
     error::Error,
     fmt::{Debug, Display},
+    hash::Hash,
     str::FromStr,
 };
 
+mod bitgrid;
+mod cycle;
+mod direction;
+mod graph;
+mod grid3;
+mod hex;
+mod image;
+mod lines;
+mod pattern;
+mod render;
+mod sparse;
+mod svg;
+mod topology;
+mod transform;
+
+pub use bitgrid::{BitGrid, Rule};
+pub use cycle::{Cycle, find_cycle};
+pub use direction::Direction;
+pub use graph::{Graph, NodeId};
+pub use grid3::{Connectivity, Grid3, Point3};
+pub use hex::{Hex, HexDirection, HexGrid};
+pub use image::{Image, Rgb};
+pub use lines::Ray;
+pub use pattern::PatternMatch;
+pub use render::{Colour, Render, Style};
+pub use sparse::{Bounds, SparseGrid};
+pub use svg::Svg;
+pub use topology::Topology;
+pub use transform::{Orientation, SubGrid};
+
 pub struct Grid<T> {
     grid: Vec<Vec<T>>,
+    topology: Topology,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -21,12 +53,16 @@ impl Error for ParseGridError {}
 
 impl<T> Grid<T> {
     pub fn new() -> Self {
-        Self { grid: Vec::new() }
+        Self {
+            grid: Vec::new(),
+            topology: Topology::Bounded,
+        }
     }
 
     pub fn with_capacity(rows: usize) -> Self {
         Self {
             grid: Vec::with_capacity(rows),
+            topology: Topology::Bounded,
         }
     }
 
@@ -57,6 +93,13 @@ impl<T> Grid<T> {
         self.grid.first().map(|r| r.len()).unwrap_or_default()
     }
 
+    /// Whether every row is as long as the first, as [`Grid::transpose`] and the other
+    /// transforms need.
+    pub fn is_rectangular(&self) -> bool {
+        let cols = self.col_count();
+        self.grid.iter().all(|r| r.len() == cols)
+    }
+
     pub fn iter_cells<'a>(&'a self) -> impl Iterator<Item = Cell<'a, T>> {
         GridIterator {
             r: 0,
@@ -65,58 +108,21 @@ impl<T> Grid<T> {
         }
     }
 
-    pub fn straight_neighbours(&self, row: usize, col: usize) -> Vec<(usize, usize)> {
-        let mut res = Vec::with_capacity(4);
-
-        if row > 0 {
-            res.push((row - 1, col));
-        }
-
-        if col > 0 {
-            res.push((row, col - 1));
-        }
-
-        if row < self.row_count() - 1 {
-            res.push((row + 1, col));
-        }
-
-        if col < self.col_count() - 1 {
-            res.push((row, col + 1))
-        }
+    /// Starts an annotated rendering of the grid, see [`Render`].
+    pub fn render(&self) -> Render<'_, T> {
+        Render::new(self)
+    }
 
-        res
+    pub fn straight_neighbours(&self, row: usize, col: usize) -> Vec<(usize, usize)> {
+        self.neighbours_towards(row, col, &Direction::STRAIGHT)
     }
 
     pub fn diagonal_neighbours(&self, row: usize, col: usize) -> Vec<(usize, usize)> {
-        let mut res = Vec::with_capacity(4);
-
-        if row > 0 {
-            if col > 0 {
-                res.push((row - 1, col - 1));
-            }
-
-            if col < self.col_count() - 1 {
-                res.push((row - 1, col + 1))
-            }
-        }
-
-        if row < self.row_count() - 1 {
-            if col > 0 {
-                res.push((row + 1, col - 1));
-            }
-
-            if col < self.col_count() - 1 {
-                res.push((row + 1, col + 1))
-            }
-        }
-
-        res
+        self.neighbours_towards(row, col, &Direction::DIAGONAL)
     }
 
     pub fn neighbours(&self, row: usize, col: usize) -> Vec<(usize, usize)> {
-        let mut result = self.straight_neighbours(row, col);
-        result.append(&mut self.diagonal_neighbours(row, col));
-        result
+        self.neighbours_towards(row, col, &Direction::ALL)
     }
 }
 
@@ -180,10 +186,32 @@ where
     fn clone(&self) -> Self {
         Self {
             grid: self.grid.clone(),
+            topology: self.topology,
         }
     }
 }
 
+impl<T> PartialEq for Grid<T>
+where
+    T: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.topology == other.topology && self.grid == other.grid
+    }
+}
+
+impl<T> Eq for Grid<T> where T: Eq {}
+
+impl<T> Hash for Grid<T>
+where
+    T: Hash,
+{
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.grid.hash(state);
+        self.topology.hash(state);
+    }
+}
+
 impl<T> Display for Grid<T>
 where
     T: Display,
@@ -205,7 +233,10 @@ where
     T: Debug,
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("Grid").field("grid", &self.grid).finish()
+        f.debug_struct("Grid")
+            .field("grid", &self.grid)
+            .field("topology", &self.topology)
+            .finish()
     }
 }
 
@@ -0,0 +1,122 @@
+use crate::{Cell, Direction, Grid, Topology};
+
+/// Cells met walking in a straight line, see [`Grid::ray`].
+pub struct Ray<'a, T> {
+    grid: &'a Grid<T>,
+    next: Option<(usize, usize)>,
+    start: (usize, usize),
+    dir: Direction,
+    topology: Topology,
+}
+
+impl<'a, T> Iterator for Ray<'a, T> {
+    type Item = Cell<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (row, col) = self.next?;
+        let cell = self.grid.get(row, col)?;
+        // On wrapping grids the ray ends once it comes back around to where it started
+        self.next = self
+            .grid
+            .step_within(row, col, self.dir, 1, self.topology)
+            .filter(|pos| *pos != self.start);
+        Some(cell)
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn row<'a>(&'a self, row: usize) -> impl Iterator<Item = Cell<'a, T>> {
+        (0..self.col_count()).filter_map(move |col| self.get(row, col))
+    }
+
+    pub fn col<'a>(&'a self, col: usize) -> impl Iterator<Item = Cell<'a, T>> {
+        (0..self.row_count()).filter_map(move |row| self.get(row, col))
+    }
+
+    /// Walks from `from` (included) in `dir` until leaving the grid, wrapping around according
+    /// to the grid's topology.
+    pub fn ray(&self, from: (usize, usize), dir: Direction) -> Ray<'_, T> {
+        self.ray_within(from, dir, self.topology)
+    }
+
+    fn ray_within(&self, from: (usize, usize), dir: Direction, topology: Topology) -> Ray<'_, T> {
+        Ray {
+            grid: self,
+            next: Some(from),
+            start: from,
+            dir,
+            topology,
+        }
+    }
+
+    /// Every diagonal going down and to the right, starting from the bottom left corner.
+    ///
+    /// Diagonals never wrap, whatever the topology, so that each cell is on exactly one of them.
+    pub fn diagonals(&self) -> impl Iterator<Item = Ray<'_, T>> {
+        let starts = (0..self.row_count())
+            .rev()
+            .map(|row| (row, 0))
+            .chain((1..self.col_count()).map(|col| (0, col)));
+        starts.map(|from| self.ray_within(from, Direction::SouthEast, Topology::Bounded))
+    }
+
+    /// Every diagonal going down and to the left, starting from the top left corner.
+    pub fn anti_diagonals(&self) -> impl Iterator<Item = Ray<'_, T>> {
+        let last_col = self.col_count().saturating_sub(1);
+        let starts = (0..self.col_count())
+            .map(|col| (0, col))
+            .chain((1..self.row_count()).map(move |row| (row, last_col)));
+        starts.map(|from| self.ray_within(from, Direction::SouthWest, Topology::Bounded))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Direction, Grid, Topology};
+
+    fn grid() -> Grid<u8> {
+        let mut grid = Grid::new();
+        grid.add_row(vec![1, 2, 3]);
+        grid.add_row(vec![4, 5, 6]);
+        grid
+    }
+
+    #[test]
+    fn test_row_col() {
+        let grid = grid();
+        assert_eq!(
+            vec![4, 5, 6],
+            grid.row(1).map(|c| *c.val).collect::<Vec<_>>()
+        );
+        assert_eq!(vec![3, 6], grid.col(2).map(|c| *c.val).collect::<Vec<_>>());
+        assert_eq!(0, grid.row(2).count());
+    }
+
+    #[test]
+    fn test_ray() {
+        let grid = grid();
+        let ray = grid.ray((0, 0), Direction::East).map(|c| *c.val);
+        assert_eq!(vec![1, 2, 3], ray.collect::<Vec<_>>());
+
+        let grid = grid.with_topology(Topology::WrapRows);
+        let ray = grid.ray((1, 1), Direction::East).map(|c| *c.val);
+        assert_eq!(vec![5, 6, 4], ray.collect::<Vec<_>>());
+        assert_eq!(0, grid.ray((5, 5), Direction::East).count());
+    }
+
+    #[test]
+    fn test_diagonals() {
+        let grid = grid().with_topology(Topology::Torus);
+        let diagonals = grid
+            .diagonals()
+            .map(|d| d.map(|c| *c.val).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(vec![vec![4], vec![1, 5], vec![2, 6], vec![3]], diagonals);
+
+        let anti = grid
+            .anti_diagonals()
+            .map(|d| d.map(|c| *c.val).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(vec![vec![1], vec![2, 4], vec![3, 5], vec![6]], anti);
+    }
+}
@@ -0,0 +1,125 @@
+use crate::{Grid, Orientation};
+
+/// Where a pattern was found by [`Grid::find_pattern_oriented`], and how it was turned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PatternMatch {
+    pub row: usize,
+    pub col: usize,
+    pub orientation: Orientation,
+}
+
+impl<T> Grid<T>
+where
+    T: PartialEq,
+{
+    /// Every top left position where `pattern` matches the grid, `None` cells matching anything.
+    pub fn find_pattern(&self, pattern: &Grid<Option<T>>) -> Vec<(usize, usize)> {
+        let (rows, cols) = (pattern.row_count(), pattern.col_count());
+        if rows == 0 || cols == 0 {
+            return vec![];
+        }
+
+        let mut res = vec![];
+        for top in 0..(self.row_count() + 1).saturating_sub(rows) {
+            for left in 0..(self.col_count() + 1).saturating_sub(cols) {
+                let matches = pattern.iter_cells().all(|cell| match cell.val {
+                    None => true,
+                    Some(expected) => self
+                        .get(top + cell.row, left + cell.col)
+                        .is_some_and(|c| c.val == expected),
+                });
+                if matches {
+                    res.push((top, left));
+                }
+            }
+        }
+        res
+    }
+}
+
+impl<T> Grid<T>
+where
+    T: PartialEq + Clone,
+{
+    /// Like [`Grid::find_pattern`], trying the pattern in all eight rotations and reflections.
+    ///
+    /// Orientations giving the same pattern as an earlier one are skipped, so a symmetric pattern
+    /// is only reported once per position. A ragged pattern can't be turned, and matches nowhere.
+    pub fn find_pattern_oriented(&self, pattern: &Grid<Option<T>>) -> Vec<PatternMatch> {
+        if !pattern.is_rectangular() {
+            return vec![];
+        }
+
+        let mut seen: Vec<Grid<Option<T>>> = vec![];
+        let mut res = vec![];
+        for orientation in Orientation::ALL {
+            let Some(oriented) = orientation.apply(pattern) else {
+                continue;
+            };
+            if seen.contains(&oriented) {
+                continue;
+            }
+
+            res.extend(
+                self.find_pattern(&oriented)
+                    .into_iter()
+                    .map(|(row, col)| PatternMatch {
+                        row,
+                        col,
+                        orientation,
+                    }),
+            );
+            seen.push(oriented);
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Grid, Orientation};
+
+    fn pattern(s: &str) -> Grid<Option<char>> {
+        let mut grid = Grid::new();
+        for line in s.lines() {
+            grid.add_row(line.chars().map(|c| (c != '?').then_some(c)).collect());
+        }
+        grid
+    }
+
+    #[test]
+    fn test_find_pattern() {
+        let grid: Grid<char> = "XMAS.\n.XMAS\nXMAX.".parse().unwrap();
+        assert_eq!(vec![(0, 0), (1, 1)], grid.find_pattern(&pattern("XMAS")));
+        assert_eq!(
+            vec![(0, 0), (1, 1), (2, 0)],
+            grid.find_pattern(&pattern("XMA?"))
+        );
+        assert_eq!(vec![(0, 0)], grid.find_pattern(&pattern("X?\n?X")));
+        assert!(grid.find_pattern(&pattern("XMASXM")).is_empty());
+    }
+
+    #[test]
+    fn test_find_pattern_oriented() {
+        let grid: Grid<char> = "S..\nA..\nM..\nX..".parse().unwrap();
+        let found = grid.find_pattern_oriented(&pattern("XMAS"));
+        assert_eq!(1, found.len());
+        assert_eq!((0, 0), (found[0].row, found[0].col));
+        assert_eq!(Orientation::RotateCcw, found[0].orientation);
+
+        // A symmetric pattern only matches once per position
+        let grid: Grid<char> = "#.#\n.#.\n#.#".parse().unwrap();
+        assert_eq!(
+            1,
+            grid.find_pattern_oriented(&pattern("#?#\n?#?\n#?#")).len()
+        );
+    }
+
+    #[test]
+    fn test_ragged_pattern() {
+        let grid: Grid<char> = "XM.\nA..\nS..".parse().unwrap();
+        assert_eq!(vec![(0, 0)], grid.find_pattern(&pattern("XM\nA")));
+        assert!(grid.find_pattern_oriented(&pattern("XM\nA")).is_empty());
+        assert_eq!(1, grid.find_pattern_oriented(&pattern("XM\nA?")).len());
+    }
+}
@@ -0,0 +1,299 @@
+use std::{
+    collections::HashMap,
+    fmt::{Display, Write},
+};
+
+use crate::Grid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Colour {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+}
+
+impl Colour {
+    fn code(&self) -> u8 {
+        match self {
+            Self::Black => 0,
+            Self::Red => 1,
+            Self::Green => 2,
+            Self::Yellow => 3,
+            Self::Blue => 4,
+            Self::Magenta => 5,
+            Self::Cyan => 6,
+            Self::White => 7,
+        }
+    }
+}
+
+/// ANSI styling applied to an overlay.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Style {
+    fg: Option<Colour>,
+    bg: Option<Colour>,
+    bold: bool,
+}
+
+impl Style {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn fg(mut self, colour: Colour) -> Self {
+        self.fg = Some(colour);
+        self
+    }
+
+    pub fn bg(mut self, colour: Colour) -> Self {
+        self.bg = Some(colour);
+        self
+    }
+
+    pub fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    fn is_plain(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn write_start(&self, f: &mut impl Write) -> std::fmt::Result {
+        let mut codes = vec![];
+        if self.bold {
+            codes.push("1".to_string());
+        }
+        if let Some(fg) = self.fg {
+            codes.push(format!("3{}", fg.code()));
+        }
+        if let Some(bg) = self.bg {
+            codes.push(format!("4{}", bg.code()));
+        }
+        write!(f, "\x1b[{}m", codes.join(";"))
+    }
+}
+
+const HEAT: [Colour; 4] = [Colour::Blue, Colour::Green, Colour::Yellow, Colour::Red];
+
+/// Builder returned by [`Grid::render`], drawing overlays on top of the cells' own `Display`.
+///
+/// Overlays are drawn in the order they are added, so later ones win on shared positions.
+pub struct Render<'a, T> {
+    grid: &'a Grid<T>,
+    overlays: HashMap<(usize, usize), (char, Style)>,
+    rulers: bool,
+    colours: bool,
+}
+
+impl<'a, T> Render<'a, T> {
+    pub(crate) fn new(grid: &'a Grid<T>) -> Self {
+        Self {
+            grid,
+            overlays: HashMap::new(),
+            rulers: false,
+            colours: true,
+        }
+    }
+
+    /// Replaces every given position with `ch`.
+    pub fn overlay<I>(mut self, positions: I, ch: char, style: Style) -> Self
+    where
+        I: IntoIterator<Item = (usize, usize)>,
+    {
+        for pos in positions {
+            self.overlays.insert(pos, (ch, style));
+        }
+        self
+    }
+
+    /// Draws a path as arrows pointing to the next step, with the last step marked `*`.
+    pub fn path<I>(mut self, path: I, style: Style) -> Self
+    where
+        I: IntoIterator<Item = (usize, usize)>,
+    {
+        let mut it = path.into_iter().peekable();
+        while let Some(pos) = it.next() {
+            let ch = match it.peek() {
+                None => '*',
+                Some(next) if next.0 < pos.0 => '^',
+                Some(next) if next.0 > pos.0 => 'v',
+                Some(next) if next.1 < pos.1 => '<',
+                Some(next) if next.1 > pos.1 => '>',
+                Some(_) => '*',
+            };
+            self.overlays.insert(pos, (ch, style));
+        }
+        self
+    }
+
+    /// Shows each value as a digit from 0 to 9 scaled to the largest value, coloured from blue
+    /// (cold) to red (hot).
+    pub fn heat_map<I>(mut self, values: I) -> Self
+    where
+        I: IntoIterator<Item = ((usize, usize), u64)>,
+    {
+        let values = values.into_iter().collect::<Vec<_>>();
+        let max = values
+            .iter()
+            .map(|(_, v)| *v)
+            .max()
+            .unwrap_or_default()
+            .max(1);
+        for (pos, value) in values {
+            let level = (value as u128 * 9 / max as u128) as u32;
+            let colour = HEAT[(level as usize * HEAT.len()) / 10];
+            let ch = char::from_digit(level, 10).unwrap();
+            self.overlays.insert(pos, (ch, Style::new().fg(colour)));
+        }
+        self
+    }
+
+    /// Adds column numbers above the grid and row numbers on its left.
+    pub fn rulers(mut self) -> Self {
+        self.rulers = true;
+        self
+    }
+
+    /// Drops the ANSI escape codes, for output that doesn't go to a terminal.
+    pub fn plain(mut self) -> Self {
+        self.colours = false;
+        self
+    }
+
+    fn write_rulers(&self, f: &mut std::fmt::Formatter<'_>, margin: usize) -> std::fmt::Result {
+        let cols = self.grid.col_count();
+        let digits = cols.saturating_sub(1).checked_ilog10().unwrap_or(0) + 1;
+        for power in (0..digits).rev() {
+            write!(f, "{:margin$} ", "")?;
+            for col in 0..cols {
+                let tens = 10usize.pow(power);
+                if power > 0 && col % tens != 0 {
+                    f.write_char(' ')?;
+                } else {
+                    write!(f, "{}", (col / tens) % 10)?;
+                }
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T> Display for Render<'_, T>
+where
+    T: Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let margin = self
+            .grid
+            .row_count()
+            .saturating_sub(1)
+            .checked_ilog10()
+            .unwrap_or(0) as usize
+            + 1;
+
+        if self.rulers {
+            self.write_rulers(f, margin)?;
+        }
+
+        for row in 0..self.grid.row_count() {
+            if self.rulers {
+                write!(f, "{row:>margin$} ")?;
+            }
+
+            // Rows can be ragged, so go by this row's own length
+            for (col, val) in self.grid.grid[row].iter().enumerate() {
+                let Some((ch, style)) = self.overlays.get(&(row, col)) else {
+                    write!(f, "{val}")?;
+                    continue;
+                };
+
+                if !self.colours || style.is_plain() {
+                    f.write_char(*ch)?;
+                } else {
+                    style.write_start(f)?;
+                    write!(f, "{ch}\x1b[0m")?;
+                }
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Colour, Grid, Style};
+
+    fn grid() -> Grid<char> {
+        "....\n....\n....".parse().unwrap()
+    }
+
+    #[test]
+    fn test_overlay_and_path() {
+        let grid = grid();
+        let out = grid
+            .render()
+            .overlay([(0, 0), (2, 3)], '#', Style::new())
+            .path([(1, 0), (1, 1), (2, 1)], Style::new())
+            .to_string();
+        assert_eq!("#...\n>v..\n.*.#\n", out);
+    }
+
+    #[test]
+    fn test_rulers() {
+        let out = grid().render().rulers().to_string();
+        assert_eq!("  0123\n0 ....\n1 ....\n2 ....\n", out);
+    }
+
+    #[test]
+    fn test_styles() {
+        let grid = grid();
+        let style = Style::new().fg(Colour::Red).bold();
+        let out = grid.render().overlay([(0, 1)], 'x', style).to_string();
+        assert_eq!(".\x1b[1;31mx\x1b[0m..\n....\n....\n", out);
+
+        let out = grid
+            .render()
+            .overlay([(0, 1)], 'x', style)
+            .plain()
+            .to_string();
+        assert_eq!(".x..\n....\n....\n", out);
+    }
+
+    #[test]
+    fn test_ragged() {
+        let mut grid = Grid::with_capacity(2);
+        grid.add_row(vec!['.', '.', '.']);
+        grid.add_row(vec!['.']);
+        let out = grid
+            .render()
+            .overlay([(0, 2), (1, 0), (1, 2)], '#', Style::new())
+            .plain()
+            .to_string();
+        assert_eq!(
+            "..#
+#
+",
+            out
+        );
+    }
+
+    #[test]
+    fn test_heat_map() {
+        let out = grid()
+            .render()
+            .heat_map([((0, 0), 0), ((0, 1), 5), ((0, 2), 10)])
+            .plain()
+            .to_string();
+        assert_eq!("049.\n....\n....\n", out);
+    }
+}
@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+
+use crate::Grid;
+
+/// Inclusive bounding box of the cells set in a [`SparseGrid`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bounds {
+    pub min_row: i64,
+    pub min_col: i64,
+    pub max_row: i64,
+    pub max_col: i64,
+}
+
+impl Bounds {
+    fn point(row: i64, col: i64) -> Self {
+        Self {
+            min_row: row,
+            min_col: col,
+            max_row: row,
+            max_col: col,
+        }
+    }
+
+    fn include(&mut self, row: i64, col: i64) {
+        self.min_row = self.min_row.min(row);
+        self.min_col = self.min_col.min(col);
+        self.max_row = self.max_row.max(row);
+        self.max_col = self.max_col.max(col);
+    }
+
+    pub fn contains(&self, row: i64, col: i64) -> bool {
+        (self.min_row..=self.max_row).contains(&row) && (self.min_col..=self.max_col).contains(&col)
+    }
+
+    /// Saturates at `usize::MAX` for bounds wider than that.
+    pub fn row_count(&self) -> usize {
+        span(self.min_row, self.max_row)
+    }
+
+    /// Saturates at `usize::MAX` for bounds wider than that.
+    pub fn col_count(&self) -> usize {
+        span(self.min_col, self.max_col)
+    }
+}
+
+fn span(min: i64, max: i64) -> usize {
+    usize::try_from(max.abs_diff(min))
+        .unwrap_or(usize::MAX)
+        .saturating_add(1)
+}
+
+/// `(row + dr, col + dc)`, unless it falls off the `i64` range.
+fn offset(row: i64, col: i64, (dr, dc): (i64, i64)) -> Option<(i64, i64)> {
+    Some((row.checked_add(dr)?, col.checked_add(dc)?))
+}
+
+/// An unbounded grid keyed by signed coordinates, where unset cells read as a default value.
+///
+/// The bounds grow to cover every cell that was ever set, even if it was later reset.
+#[derive(Debug, Clone)]
+pub struct SparseGrid<T> {
+    cells: HashMap<(i64, i64), T>,
+    default: T,
+    bounds: Option<Bounds>,
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new(default: T) -> Self {
+        Self {
+            cells: HashMap::new(),
+            default,
+            bounds: None,
+        }
+    }
+
+    pub fn get(&self, row: i64, col: i64) -> &T {
+        self.cells.get(&(row, col)).unwrap_or(&self.default)
+    }
+
+    pub fn set(&mut self, row: i64, col: i64, val: T) {
+        match self.bounds.as_mut() {
+            Some(bounds) => bounds.include(row, col),
+            None => self.bounds = Some(Bounds::point(row, col)),
+        }
+        self.cells.insert((row, col), val);
+    }
+
+    /// Resets a cell to the default value, returning what was there.
+    pub fn remove(&mut self, row: i64, col: i64) -> Option<T> {
+        self.cells.remove(&(row, col))
+    }
+
+    pub fn bounds(&self) -> Option<Bounds> {
+        self.bounds
+    }
+
+    /// Number of cells explicitly set.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Iterates over the cells explicitly set, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = ((i64, i64), &T)> {
+        self.cells.iter().map(|(pos, val)| (*pos, val))
+    }
+
+    /// The neighbours up, left, down and right, leaving out any past the edge of the `i64` range.
+    pub fn straight_neighbours(&self, row: i64, col: i64) -> Vec<(i64, i64)> {
+        [(-1, 0), (0, -1), (1, 0), (0, 1)]
+            .into_iter()
+            .filter_map(|d| offset(row, col, d))
+            .collect()
+    }
+
+    pub fn diagonal_neighbours(&self, row: i64, col: i64) -> Vec<(i64, i64)> {
+        [(-1, -1), (-1, 1), (1, -1), (1, 1)]
+            .into_iter()
+            .filter_map(|d| offset(row, col, d))
+            .collect()
+    }
+
+    pub fn neighbours(&self, row: i64, col: i64) -> Vec<(i64, i64)> {
+        let mut result = self.straight_neighbours(row, col);
+        result.append(&mut self.diagonal_neighbours(row, col));
+        result
+    }
+}
+
+impl<T> SparseGrid<T>
+where
+    T: Clone,
+{
+    /// The most cells [`SparseGrid::try_to_grid`] fills in; a few scattered cells far apart
+    /// would otherwise ask for more memory than there is.
+    pub const MAX_CELLS: usize = 1 << 26;
+
+    /// Copies the cells within the bounds into a dense grid, whose `(0, 0)` is the bounds' minimum.
+    ///
+    /// Panics if the bounds hold more than [`SparseGrid::MAX_CELLS`] cells.
+    pub fn to_grid(&self) -> Grid<T> {
+        self.try_to_grid()
+            .expect("A dense grid should have at most SparseGrid::MAX_CELLS cells")
+    }
+
+    /// Like [`SparseGrid::to_grid`], but `None` if the bounds hold more than
+    /// [`SparseGrid::MAX_CELLS`] cells.
+    pub fn try_to_grid(&self) -> Option<Grid<T>> {
+        let Some(bounds) = self.bounds else {
+            return Some(Grid::new());
+        };
+        let cells = bounds.row_count().checked_mul(bounds.col_count())?;
+        if cells > Self::MAX_CELLS {
+            return None;
+        }
+
+        let mut grid = Grid::with_capacity(bounds.row_count());
+        for row in bounds.min_row..=bounds.max_row {
+            grid.add_row(
+                (bounds.min_col..=bounds.max_col)
+                    .map(|col| self.get(row, col).clone())
+                    .collect(),
+            );
+        }
+        Some(grid)
+    }
+}
+
+impl<T> SparseGrid<T>
+where
+    T: PartialEq,
+{
+    /// Keeps the cells of a dense grid that differ from `default`, with the same coordinates.
+    pub fn from_grid(grid: Grid<T>, default: T) -> Self {
+        let mut sparse = Self::new(default);
+        for (row, cells) in grid.grid.into_iter().enumerate() {
+            for (col, val) in cells.into_iter().enumerate() {
+                if val != sparse.default {
+                    sparse.set(row as i64, col as i64, val);
+                }
+            }
+        }
+        sparse
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Bounds, Grid, SparseGrid};
+
+    #[test]
+    fn test_bounds() {
+        let mut grid = SparseGrid::new('.');
+        assert_eq!(None, grid.bounds());
+        grid.set(-2, 5, '#');
+        grid.set(3, -1, '#');
+        assert_eq!(
+            Some(Bounds {
+                min_row: -2,
+                min_col: -1,
+                max_row: 3,
+                max_col: 5
+            }),
+            grid.bounds()
+        );
+        assert_eq!(&'#', grid.get(-2, 5));
+        assert_eq!(&'.', grid.get(100_000, -100_000));
+        assert_eq!(8, grid.neighbours(0, 0).len());
+    }
+
+    #[test]
+    fn test_dense_round_trip() {
+        let dense: Grid<char> = "..#\n#..".parse().unwrap();
+        let sparse = SparseGrid::from_grid(dense, '.');
+        assert_eq!(2, sparse.len());
+        assert_eq!("..#\n#..\n", sparse.to_grid().to_string());
+
+        let mut sparse = sparse;
+        sparse.set(-1, 3, '@');
+        assert_eq!("...@\n..#.\n#...\n", sparse.to_grid().to_string());
+    }
+
+    #[test]
+    fn test_too_large() {
+        let mut grid = SparseGrid::new('.');
+        grid.set(0, 0, '#');
+        grid.set(1 << 40, 1 << 40, '#');
+        assert!(grid.try_to_grid().is_none());
+
+        grid.set(i64::MIN, 0, '#');
+        grid.set(i64::MAX, 0, '#');
+        assert_eq!(usize::MAX, grid.bounds().unwrap().row_count());
+        assert!(grid.try_to_grid().is_none());
+        assert_eq!(
+            Some("#\n".to_string()),
+            SparseGrid::from_grid("#".parse().unwrap(), '.')
+                .try_to_grid()
+                .map(|g| g.to_string())
+        );
+    }
+
+    #[test]
+    fn test_edge_neighbours() {
+        let grid = SparseGrid::new('.');
+        assert_eq!(5, grid.neighbours(i64::MIN, 0).len());
+        assert_eq!(3, grid.neighbours(i64::MAX, i64::MIN).len());
+        assert_eq!(
+            vec![(i64::MAX - 1, 0), (i64::MAX, -1), (i64::MAX, 1)],
+            grid.straight_neighbours(i64::MAX, 0)
+        );
+    }
+}
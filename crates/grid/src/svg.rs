@@ -0,0 +1,108 @@
+use std::fmt::{Display, Write};
+
+/// Collects polygons, segments and rectangles over integer points and writes them as an SVG.
+///
+/// Coordinates keep the puzzle orientation (y grows downwards) and the view box is fitted to
+/// everything drawn, so huge coordinates need no scaling beforehand.
+#[derive(Debug, Default)]
+pub struct Svg {
+    shapes: Vec<String>,
+    min: Option<(i64, i64)>,
+    max: Option<(i64, i64)>,
+}
+
+impl Svg {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A closed polygon going through every point in order.
+    pub fn polygon(mut self, points: &[(i64, i64)], stroke: &str, fill: &str) -> Self {
+        let mut attr = String::new();
+        for (x, y) in points {
+            self.extend(*x, *y);
+            write!(attr, "{x},{y} ").unwrap();
+        }
+        self.shapes.push(format!(
+            r#"<polygon points="{}" stroke="{stroke}" fill="{fill}" vector-effect="non-scaling-stroke"/>"#,
+            attr.trim_end()
+        ));
+        self
+    }
+
+    pub fn segment(mut self, a: (i64, i64), b: (i64, i64), stroke: &str) -> Self {
+        self.extend(a.0, a.1);
+        self.extend(b.0, b.1);
+        self.shapes.push(format!(
+            r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{stroke}" vector-effect="non-scaling-stroke"/>"#,
+            a.0, a.1, b.0, b.1
+        ));
+        self
+    }
+
+    /// The rectangle having `a` and `b` as opposite corners.
+    pub fn rect(mut self, a: (i64, i64), b: (i64, i64), stroke: &str, fill: &str) -> Self {
+        self.extend(a.0, a.1);
+        self.extend(b.0, b.1);
+        self.shapes.push(format!(
+            r#"<rect x="{}" y="{}" width="{}" height="{}" stroke="{stroke}" fill="{fill}" vector-effect="non-scaling-stroke"/>"#,
+            a.0.min(b.0),
+            a.1.min(b.1),
+            a.0.abs_diff(b.0),
+            a.1.abs_diff(b.1)
+        ));
+        self
+    }
+
+    fn extend(&mut self, x: i64, y: i64) {
+        let (min_x, min_y) = self.min.unwrap_or((x, y));
+        let (max_x, max_y) = self.max.unwrap_or((x, y));
+        self.min = Some((min_x.min(x), min_y.min(y)));
+        self.max = Some((max_x.max(x), max_y.max(y)));
+    }
+}
+
+impl Display for Svg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (min_x, min_y) = self.min.unwrap_or_default();
+        let (max_x, max_y) = self.max.unwrap_or_default();
+        // Leave a small margin so strokes on the bounds stay visible
+        let margin = ((max_x - min_x).max(max_y - min_y) / 50).max(1);
+        writeln!(
+            f,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}">"#,
+            min_x - margin,
+            min_y - margin,
+            max_x - min_x + 2 * margin,
+            max_y - min_y + 2 * margin
+        )?;
+        for shape in self.shapes.iter() {
+            writeln!(f, "  {shape}")?;
+        }
+        writeln!(f, "</svg>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Svg;
+
+    #[test]
+    fn test_svg() {
+        let svg = Svg::new()
+            .polygon(&[(0, 0), (100, 0), (100, 50)], "black", "none")
+            .rect((10, 40), (20, 5), "red", "none")
+            .segment((0, 0), (-50, 50), "blue")
+            .to_string();
+
+        let lines = svg.lines().collect::<Vec<_>>();
+        assert_eq!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="-53 -3 156 56">"#,
+            lines[0]
+        );
+        assert!(lines[1].contains(r#"points="0,0 100,0 100,50""#));
+        assert!(lines[2].contains(r#"x="10" y="5" width="10" height="35""#));
+        assert!(lines[3].contains(r#"x2="-50" y2="50""#));
+        assert_eq!("</svg>", lines[4]);
+    }
+}
@@ -0,0 +1,194 @@
+use crate::{Direction, Grid};
+
+/// How the edges of a [`Grid`] connect.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Topology {
+    /// Stepping off an edge leaves the grid.
+    #[default]
+    Bounded,
+    /// Stepping off the end of a row comes back at its start, and the other way around.
+    WrapRows,
+    /// Stepping off the bottom of a column comes back at its top, and the other way around.
+    WrapCols,
+    /// Both rows and columns wrap around.
+    Torus,
+}
+
+impl Topology {
+    fn wraps_rows(&self) -> bool {
+        matches!(self, Self::WrapRows | Self::Torus)
+    }
+
+    fn wraps_cols(&self) -> bool {
+        matches!(self, Self::WrapCols | Self::Torus)
+    }
+
+    /// The same edges once rows and columns have swapped places.
+    pub fn transposed(&self) -> Self {
+        match self {
+            Self::WrapRows => Self::WrapCols,
+            Self::WrapCols => Self::WrapRows,
+            other => *other,
+        }
+    }
+}
+
+/// Moves `n` steps from `pos` towards the end of an axis of `len` cells when `dir` is positive,
+/// towards its start when negative. A wrapping axis only looks at `n` modulo its length.
+fn move_along(pos: usize, dir: i64, n: usize, len: usize, wraps: bool) -> Option<usize> {
+    if len == 0 {
+        return None;
+    }
+
+    if wraps {
+        let (pos, n) = (pos % len, n % len);
+        return Some(match dir.signum() {
+            1 => (pos + n) % len,
+            -1 => (pos + (len - n)) % len,
+            _ => pos,
+        });
+    }
+
+    match dir.signum() {
+        1 => pos.checked_add(n),
+        -1 => pos.checked_sub(n),
+        _ => Some(pos),
+    }
+    .filter(|next| *next < len)
+}
+
+impl<T> Grid<T> {
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// The position one step away in `dir`, if it is still on the grid.
+    pub fn step(&self, row: usize, col: usize, dir: Direction) -> Option<(usize, usize)> {
+        self.step_by(row, col, dir, 1)
+    }
+
+    /// The position `n` steps away in `dir`, if it is still on the grid.
+    pub fn step_by(
+        &self,
+        row: usize,
+        col: usize,
+        dir: Direction,
+        n: usize,
+    ) -> Option<(usize, usize)> {
+        self.step_within(row, col, dir, n, self.topology)
+    }
+
+    pub(crate) fn step_within(
+        &self,
+        row: usize,
+        col: usize,
+        dir: Direction,
+        n: usize,
+        topology: Topology,
+    ) -> Option<(usize, usize)> {
+        let (dr, dc) = dir.delta();
+        let row = move_along(row, dr, n, self.row_count(), topology.wraps_cols())?;
+        let col = move_along(col, dc, n, self.col_count(), topology.wraps_rows())?;
+        Some((row, col))
+    }
+
+    /// Distinct positions one step away in any of `dirs`, never including the starting one.
+    pub(crate) fn neighbours_towards(
+        &self,
+        row: usize,
+        col: usize,
+        dirs: &[Direction],
+    ) -> Vec<(usize, usize)> {
+        let mut res = Vec::with_capacity(dirs.len());
+        for dir in dirs {
+            if let Some(pos) = self.step(row, col, *dir)
+                && pos != (row, col)
+                && !res.contains(&pos)
+            {
+                res.push(pos);
+            }
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Direction, Grid, Topology};
+
+    fn grid(topology: Topology) -> Grid<u8> {
+        let mut grid = Grid::new();
+        grid.add_row(vec![1, 2, 3, 4]);
+        grid.add_row(vec![5, 6, 7, 8]);
+        grid.add_row(vec![9, 10, 11, 12]);
+        grid.with_topology(topology)
+    }
+
+    #[test]
+    fn test_step() {
+        let bounded = grid(Topology::Bounded);
+        assert_eq!(None, bounded.step(0, 3, Direction::East));
+        assert_eq!(Some((1, 2)), bounded.step(0, 3, Direction::SouthWest));
+
+        let rows = grid(Topology::WrapRows);
+        assert_eq!(Some((0, 0)), rows.step(0, 3, Direction::East));
+        assert_eq!(None, rows.step(0, 3, Direction::North));
+
+        let cols = grid(Topology::WrapCols);
+        assert_eq!(Some((2, 3)), cols.step(0, 3, Direction::North));
+        assert_eq!(None, cols.step(0, 3, Direction::East));
+
+        let torus = grid(Topology::Torus);
+        assert_eq!(Some((2, 0)), torus.step(0, 3, Direction::NorthEast));
+        assert_eq!(Some((0, 1)), torus.step_by(0, 0, Direction::West, 103));
+    }
+
+    #[test]
+    fn test_step_far() {
+        // Only the remainder of a long walk matters on a wrapping axis
+        let rows = grid(Topology::WrapRows);
+        assert_eq!(
+            Some((0, 3)),
+            rows.step_by(0, 0, Direction::East, usize::MAX)
+        );
+        assert_eq!(
+            Some((0, 1)),
+            rows.step_by(0, 0, Direction::West, usize::MAX)
+        );
+        assert_eq!(None, rows.step_by(0, 0, Direction::South, usize::MAX));
+
+        let bounded = grid(Topology::Bounded);
+        assert_eq!(
+            None,
+            bounded.step_by(2, 3, Direction::SouthEast, usize::MAX)
+        );
+        assert_eq!(
+            None,
+            bounded.step_by(2, 3, Direction::NorthWest, usize::MAX)
+        );
+        assert_eq!(Some((0, 1)), bounded.step_by(2, 3, Direction::NorthWest, 2));
+    }
+
+    #[test]
+    fn test_neighbours() {
+        assert_eq!(3, grid(Topology::Bounded).neighbours(0, 0).len());
+        assert_eq!(5, grid(Topology::WrapRows).neighbours(0, 0).len());
+        assert_eq!(8, grid(Topology::Torus).neighbours(0, 0).len());
+        assert_eq!(
+            vec![(2, 0), (0, 3), (1, 0), (0, 1)],
+            grid(Topology::Torus).straight_neighbours(0, 0)
+        );
+
+        // On a single column, wrapping rows only leads back to the same cell
+        let mut narrow = Grid::new();
+        narrow.add_row(vec![1]);
+        narrow.add_row(vec![2]);
+        let narrow = narrow.with_topology(Topology::Torus);
+        assert_eq!(vec![(1, 0)], narrow.neighbours(0, 0));
+    }
+}
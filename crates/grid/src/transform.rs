@@ -0,0 +1,370 @@
+use std::fmt::Display;
+
+use crate::{Cell, Grid, Topology};
+
+impl<T> Grid<T>
+where
+    T: Clone,
+{
+    /// A `rows` by `cols` grid with `f(row, col)` in every cell, unless this one is ragged and
+    /// `f` can't read it as a rectangle.
+    fn reshape<F>(&self, rows: usize, cols: usize, topology: Topology, f: F) -> Option<Self>
+    where
+        F: Fn(usize, usize) -> T,
+    {
+        if !self.is_rectangular() {
+            return None;
+        }
+
+        let mut grid = Grid::with_capacity(rows);
+        for row in 0..rows {
+            grid.add_row((0..cols).map(|col| f(row, col)).collect());
+        }
+        grid.topology = topology;
+        Some(grid)
+    }
+
+    fn at(&self, row: usize, col: usize) -> T {
+        self.grid[row][col].clone()
+    }
+
+    /// Swaps rows and columns, so that row `i` of the result is column `i` of the grid.
+    ///
+    /// Like every transform, `None` if the rows aren't all the same length.
+    pub fn transpose(&self) -> Option<Self> {
+        self.reshape(
+            self.col_count(),
+            self.row_count(),
+            self.topology.transposed(),
+            |r, c| self.at(c, r),
+        )
+    }
+
+    /// Rotates a quarter turn clockwise: the first column, read bottom to top, becomes the first row.
+    pub fn rotate_cw(&self) -> Option<Self> {
+        let rows = self.row_count();
+        self.reshape(
+            self.col_count(),
+            rows,
+            self.topology.transposed(),
+            |r, c| self.at(rows - 1 - c, r),
+        )
+    }
+
+    /// Rotates a quarter turn counter-clockwise: the last column, read top to bottom, becomes the
+    /// first row.
+    pub fn rotate_ccw(&self) -> Option<Self> {
+        let cols = self.col_count();
+        self.reshape(
+            cols,
+            self.row_count(),
+            self.topology.transposed(),
+            |r, c| self.at(c, cols - 1 - r),
+        )
+    }
+
+    /// Mirrors left to right.
+    pub fn flip_h(&self) -> Option<Self> {
+        let cols = self.col_count();
+        self.reshape(self.row_count(), cols, self.topology, |r, c| {
+            self.at(r, cols - 1 - c)
+        })
+    }
+
+    /// Mirrors top to bottom.
+    pub fn flip_v(&self) -> Option<Self> {
+        let rows = self.row_count();
+        self.reshape(rows, self.col_count(), self.topology, |r, c| {
+            self.at(rows - 1 - r, c)
+        })
+    }
+}
+
+/// One of the eight ways to rotate and reflect a grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Orientation {
+    Identity,
+    RotateCw,
+    Rotate180,
+    RotateCcw,
+    FlipH,
+    FlipV,
+    /// Reflection across the main diagonal.
+    Transpose,
+    /// Reflection across the anti-diagonal.
+    AntiTranspose,
+}
+
+impl Orientation {
+    pub const ALL: [Orientation; 8] = [
+        Self::Identity,
+        Self::RotateCw,
+        Self::Rotate180,
+        Self::RotateCcw,
+        Self::FlipH,
+        Self::FlipV,
+        Self::Transpose,
+        Self::AntiTranspose,
+    ];
+
+    /// `None` for a ragged grid, like the transforms it is made of.
+    pub fn apply<T: Clone>(&self, grid: &Grid<T>) -> Option<Grid<T>> {
+        match self {
+            Self::Identity => grid.is_rectangular().then(|| grid.clone()),
+            Self::RotateCw => grid.rotate_cw(),
+            Self::Rotate180 => grid.flip_h()?.flip_v(),
+            Self::RotateCcw => grid.rotate_ccw(),
+            Self::FlipH => grid.flip_h(),
+            Self::FlipV => grid.flip_v(),
+            Self::Transpose => grid.transpose(),
+            Self::AntiTranspose => grid.rotate_ccw()?.flip_h(),
+        }
+    }
+}
+
+/// A borrowed rectangular window into a [`Grid`], addressed relative to its top left corner.
+pub struct SubGrid<'a, T> {
+    grid: &'a Grid<T>,
+    top: usize,
+    left: usize,
+    rows: usize,
+    cols: usize,
+}
+
+impl<'a, T> SubGrid<'a, T> {
+    /// Position of the window's top left corner in the underlying grid.
+    pub fn origin(&self) -> (usize, usize) {
+        (self.top, self.left)
+    }
+
+    pub fn row_count(&self) -> usize {
+        self.rows
+    }
+
+    pub fn col_count(&self) -> usize {
+        self.cols
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Option<Cell<'a, T>> {
+        if row >= self.rows || col >= self.cols {
+            return None;
+        }
+
+        self.grid
+            .get(self.top + row, self.left + col)
+            .map(|cell| Cell {
+                row,
+                col,
+                val: cell.val,
+            })
+    }
+
+    pub fn iter_cells(&self) -> impl Iterator<Item = Cell<'a, T>> {
+        let (rows, cols) = (self.rows, self.cols);
+        let window = *self;
+        (0..rows).flat_map(move |row| (0..cols).filter_map(move |col| window.get(row, col)))
+    }
+}
+
+impl<T> Clone for SubGrid<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for SubGrid<'_, T> {}
+
+impl<T> SubGrid<'_, T>
+where
+    T: Clone,
+{
+    pub fn to_grid(&self) -> Grid<T> {
+        let mut grid = Grid::with_capacity(self.rows);
+        for row in 0..self.rows {
+            grid.add_row(
+                (0..self.cols)
+                    .filter_map(|col| self.get(row, col))
+                    .map(|cell| cell.val.clone())
+                    .collect(),
+            );
+        }
+        grid
+    }
+}
+
+impl<T> Display for SubGrid<'_, T>
+where
+    T: Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in 0..self.rows {
+            for cell in (0..self.cols).filter_map(|col| self.get(row, col)) {
+                write!(f, "{}", cell.val)?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T> Grid<T> {
+    /// The `rows` by `cols` window whose top left corner is at `(top, left)`, if every one of its
+    /// cells is on the grid.
+    pub fn subgrid(
+        &self,
+        top: usize,
+        left: usize,
+        rows: usize,
+        cols: usize,
+    ) -> Option<SubGrid<'_, T>> {
+        let bottom = top.checked_add(rows)?;
+        let right = left.checked_add(cols)?;
+        if bottom > self.row_count() || self.grid[top..bottom].iter().any(|r| r.len() < right) {
+            return None;
+        }
+
+        Some(SubGrid {
+            grid: self,
+            top,
+            left,
+            rows,
+            cols,
+        })
+    }
+
+    /// Every `rows` by `cols` window fitting in the grid, in row-major order of their top left
+    /// corner.
+    pub fn windows(&self, rows: usize, cols: usize) -> impl Iterator<Item = SubGrid<'_, T>> {
+        let tops = (self.row_count() + 1).saturating_sub(rows);
+        let lefts = (self.col_count() + 1).saturating_sub(cols);
+        (0..tops).flat_map(move |top| {
+            (0..lefts).filter_map(move |left| self.subgrid(top, left, rows, cols))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{Direction, Grid, Orientation, Topology};
+
+    fn grid() -> Grid<char> {
+        "abc\ndef".parse().unwrap()
+    }
+
+    #[test]
+    fn test_transforms() {
+        let grid = grid();
+        let show = |g: Option<Grid<char>>| g.unwrap().to_string();
+        assert_eq!("ad\nbe\ncf\n", show(grid.transpose()));
+        assert_eq!("da\neb\nfc\n", show(grid.rotate_cw()));
+        assert_eq!("cf\nbe\nad\n", show(grid.rotate_ccw()));
+        assert_eq!("cba\nfed\n", show(grid.flip_h()));
+        assert_eq!("def\nabc\n", show(grid.flip_v()));
+        assert_eq!(
+            grid.to_string(),
+            show(
+                grid.rotate_cw()
+                    .and_then(|g| g.rotate_cw())
+                    .and_then(|g| g.rotate_ccw())
+                    .and_then(|g| g.rotate_ccw())
+            )
+        );
+    }
+
+    #[test]
+    fn test_ragged() {
+        let ragged: Grid<char> = "abc
+d
+ef"
+        .parse()
+        .unwrap();
+        assert!(!ragged.is_rectangular());
+        assert_eq!(None, ragged.transpose());
+        assert_eq!(None, ragged.rotate_cw());
+        assert_eq!(None, ragged.rotate_ccw());
+        assert_eq!(None, ragged.flip_h());
+        assert_eq!(None, ragged.flip_v());
+        for orientation in Orientation::ALL {
+            assert_eq!(None, orientation.apply(&ragged));
+        }
+
+        // Windows only cover cells that are there
+        assert!(ragged.subgrid(0, 0, 2, 2).is_none());
+        assert_eq!("a\nd\ne\n", ragged.subgrid(0, 0, 3, 1).unwrap().to_string());
+        assert_eq!(
+            "ef\n",
+            ragged.subgrid(2, 0, 1, 2).unwrap().to_grid().to_string()
+        );
+        assert_eq!(3, ragged.windows(1, 2).count());
+    }
+
+    #[test]
+    fn test_topology() {
+        let grid = grid().with_topology(Topology::WrapRows);
+        assert_eq!(Some((0, 0)), grid.step(0, 2, Direction::East));
+
+        // The row that wrapped is now a column
+        let transposed = grid.transpose().unwrap();
+        assert_eq!(Topology::WrapCols, transposed.topology());
+        assert_eq!(Some((0, 0)), transposed.step(2, 0, Direction::South));
+        assert_eq!(Topology::WrapCols, grid.rotate_cw().unwrap().topology());
+        assert_eq!(Topology::WrapCols, grid.rotate_ccw().unwrap().topology());
+        assert_eq!(Topology::WrapRows, grid.flip_h().unwrap().topology());
+        for orientation in Orientation::ALL {
+            let expected = match orientation {
+                Orientation::RotateCw
+                | Orientation::RotateCcw
+                | Orientation::Transpose
+                | Orientation::AntiTranspose => Topology::WrapCols,
+                _ => Topology::WrapRows,
+            };
+            assert_eq!(expected, orientation.apply(&grid).unwrap().topology());
+        }
+
+        let torus = grid.with_topology(Topology::Torus);
+        assert_eq!(Topology::Torus, torus.rotate_cw().unwrap().topology());
+    }
+
+    #[test]
+    fn test_orientations() {
+        let grid = grid();
+        assert_eq!(
+            "fed\ncba\n",
+            Orientation::Rotate180.apply(&grid).unwrap().to_string()
+        );
+        assert_eq!(
+            "fc\neb\nda\n",
+            Orientation::AntiTranspose.apply(&grid).unwrap().to_string()
+        );
+
+        let all = Orientation::ALL
+            .iter()
+            .map(|o| o.apply(&grid).unwrap().to_string())
+            .collect::<HashSet<_>>();
+        assert_eq!(8, all.len());
+    }
+
+    #[test]
+    fn test_windows() {
+        let grid = grid();
+        let windows = grid.windows(2, 2).collect::<Vec<_>>();
+        assert_eq!(2, windows.len());
+        assert_eq!((0, 1), windows[1].origin());
+        assert_eq!("bc\nef\n", windows[1].to_string());
+        assert_eq!(&'f', windows[1].get(1, 1).unwrap().val);
+        assert!(windows[1].get(0, 2).is_none());
+        assert_eq!(
+            vec!['b', 'c', 'e', 'f'],
+            windows[1].iter_cells().map(|c| *c.val).collect::<Vec<_>>()
+        );
+
+        assert_eq!(0, grid.windows(3, 1).count());
+        assert!(grid.subgrid(1, 1, 1, 2).is_some());
+        assert!(grid.subgrid(1, 2, 1, 2).is_none());
+        assert!(grid.subgrid(usize::MAX, 0, 1, 1).is_none());
+        assert!(grid.subgrid(0, 1, 1, usize::MAX).is_none());
+    }
+}
@@ -0,0 +1,303 @@
+use std::{error::Error, fmt::Display, str::FromStr};
+
+mod pattern;
+
+pub use pattern::{PatternError, field, match_pattern};
+
+/// A line of the input that couldn't be parsed, and why.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based line number in the whole input.
+    pub line: usize,
+    pub text: String,
+    pub reason: String,
+}
+
+impl ParseError {
+    pub fn new(line: usize, text: &str, reason: impl Display) -> Self {
+        Self {
+            line,
+            text: text.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+
+    /// Moves the error down by `lines`, for errors raised while parsing part of the input.
+    pub fn offset(mut self, lines: usize) -> Self {
+        self.line += lines;
+        self
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {} in {:?}", self.line, self.reason, self.text)
+    }
+}
+
+impl Error for ParseError {}
+
+pub type Result<T> = std::result::Result<T, ParseError>;
+
+/// Parses every line with `f`, attaching the line number to the first failure.
+pub fn lines<T, F, E>(s: &str, mut f: F) -> Result<Vec<T>>
+where
+    F: FnMut(&str) -> std::result::Result<T, E>,
+    E: Display,
+{
+    s.lines()
+        .enumerate()
+        .map(|(i, line)| f(line).map_err(|e| ParseError::new(i + 1, line, e)))
+        .collect()
+}
+
+/// Parses a single value, with a message naming what was expected.
+pub fn value<T>(s: &str) -> std::result::Result<T, String>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    s.trim()
+        .parse()
+        .map_err(|e| format!("bad value {:?}: {e}", s.trim()))
+}
+
+/// Numbers separated by whitespace or commas, over any number of lines.
+pub fn numbers<T>(s: &str) -> Result<Vec<T>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let rows = lines(s, |line| {
+        line.split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|n| !n.is_empty())
+            .map(value)
+            .collect::<std::result::Result<Vec<T>, _>>()
+    })?;
+    Ok(rows.into_iter().flatten().collect())
+}
+
+/// A single `a-b` range, returned as its two ends.
+pub fn range<T>(s: &str) -> std::result::Result<(T, T), String>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let Some((from, to)) = s.trim().split_once('-') else {
+        return Err("expected a range like a-b".to_string());
+    };
+    Ok((value(from)?, value(to)?))
+}
+
+/// `a-b` ranges, separated by commas or newlines.
+pub fn ranges<T>(s: &str) -> Result<Vec<(T, T)>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let rows = lines(s, |line| {
+        line.split(',')
+            .filter(|r| !r.trim().is_empty())
+            .map(range)
+            .collect::<std::result::Result<Vec<_>, _>>()
+    })?;
+    Ok(rows.into_iter().flatten().collect())
+}
+
+/// A single point with `N` comma-separated coordinates, such as `x,y` or `x,y,z`.
+pub fn point<T, const N: usize>(s: &str) -> std::result::Result<[T; N], String>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let coords = s
+        .split(',')
+        .map(value)
+        .collect::<std::result::Result<Vec<T>, _>>()?;
+    let found = coords.len();
+    coords
+        .try_into()
+        .map_err(|_| format!("expected {N} coordinates, found {found}"))
+}
+
+/// One point per line.
+pub fn points<T, const N: usize>(s: &str) -> Result<Vec<[T; N]>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    lines(s, point)
+}
+
+/// Rows of whitespace-separated values, which must all have the same length.
+pub fn table<T>(s: &str) -> Result<Vec<Vec<T>>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let mut width = None;
+    lines(s, |line| {
+        let row = line
+            .split_whitespace()
+            .map(value)
+            .collect::<std::result::Result<Vec<T>, _>>()?;
+        same_width(&mut width, row)
+    })
+}
+
+/// A rectangle of characters, each turned into a cell by `f`.
+pub fn chars<T, F, E>(s: &str, mut f: F) -> Result<Vec<Vec<T>>>
+where
+    F: FnMut(char) -> std::result::Result<T, E>,
+    E: Display,
+{
+    let mut width = None;
+    lines(s, |line| {
+        let row = line
+            .chars()
+            .enumerate()
+            .map(|(col, ch)| f(ch).map_err(|e| format!("column {}: {e}", col + 1)))
+            .collect::<std::result::Result<Vec<T>, _>>()?;
+        same_width(&mut width, row)
+    })
+}
+
+/// Checks that `row` is as long as the first one seen.
+fn same_width<T>(width: &mut Option<usize>, row: Vec<T>) -> std::result::Result<Vec<T>, String> {
+    match *width {
+        Some(w) if w != row.len() => Err(format!("expected {w} columns, found {}", row.len())),
+        _ => {
+            *width = Some(row.len());
+            Ok(row)
+        }
+    }
+}
+
+/// A block of lines from [`sections`], which remembers where it started.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Section<'a> {
+    pub text: &'a str,
+    /// Number of lines of the input before this section.
+    pub skipped: usize,
+}
+
+impl<'a> Section<'a> {
+    /// Runs one of the parsers on the section, with line numbers counted from the whole input.
+    pub fn parse<T, F>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&'a str) -> Result<T>,
+    {
+        f(self.text).map_err(|e| e.offset(self.skipped))
+    }
+}
+
+/// Splits the input on blank lines.
+pub fn sections(s: &str) -> Vec<Section<'_>> {
+    let mut res = vec![];
+    let mut start = None;
+    let mut offset = 0;
+    for (i, line) in s.split_inclusive('\n').enumerate() {
+        if line.trim().is_empty() {
+            if let Some((skipped, from)) = start.take() {
+                res.push(Section {
+                    text: &s[from..offset],
+                    skipped,
+                });
+            }
+        } else if start.is_none() {
+            start = Some((i, offset));
+        }
+        offset += line.len();
+    }
+
+    if let Some((skipped, from)) = start {
+        res.push(Section {
+            text: &s[from..],
+            skipped,
+        });
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ParseError, chars, lines, numbers, point, points, ranges, sections, table};
+
+    #[test]
+    fn test_numbers() {
+        assert_eq!(vec![1, 2, 3, 4], numbers::<u8>("1, 2\n3 4\n").unwrap());
+        let err = numbers::<u8>("1\n2\n3 x\n").unwrap_err();
+        assert_eq!(3, err.line);
+        assert_eq!("3 x", err.text);
+    }
+
+    #[test]
+    fn test_ranges() {
+        assert_eq!(
+            vec![(11, 22), (95, 115), (3, 5)],
+            ranges::<u64>("11-22,95-115,\n3-5").unwrap()
+        );
+        let err = ranges::<u64>("1-2\n3:4").unwrap_err();
+        assert_eq!(
+            "line 2: expected a range like a-b in \"3:4\"",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn test_points() {
+        assert_eq!(Ok([1, -2, 3]), point::<i64, 3>("1,-2, 3"));
+        assert!(point::<i64, 3>("1,2").is_err());
+        assert_eq!(
+            vec![[7, 1], [11, 1]],
+            points::<u64, 2>("7,1\n11,1").unwrap()
+        );
+        assert_eq!(2, points::<u64, 2>("7,1\n11,1,2").unwrap_err().line);
+    }
+
+    #[test]
+    fn test_table() {
+        assert_eq!(
+            vec![vec![123, 328], vec![45, 64]],
+            table::<u32>("123 328\n 45  64\n").unwrap()
+        );
+        assert_eq!(2, table::<u32>("1 2\n3\n").unwrap_err().line);
+        assert_eq!(vec![vec!["*", "+"]], table::<String>("*   +  ").unwrap());
+    }
+
+    #[test]
+    fn test_chars() {
+        let cell = |ch| match ch {
+            '.' => Ok(false),
+            '#' => Ok(true),
+            _ => Err(format!("unknown cell {ch:?}")),
+        };
+        assert_eq!(
+            vec![vec![false, true], vec![true, false]],
+            chars(".#\n#.\n", cell).unwrap()
+        );
+        assert_eq!(
+            "line 2: column 2: unknown cell 'x' in \"#x\"",
+            chars(".#\n#x", cell).unwrap_err().to_string()
+        );
+        assert_eq!(2, chars(".#\n#", cell).unwrap_err().line);
+    }
+
+    #[test]
+    fn test_sections() {
+        let input = "3-5\n10-14\n\n\n1\nx\n";
+        let parts = sections(input);
+        assert_eq!(2, parts.len());
+        assert_eq!("3-5\n10-14\n", parts[0].text);
+        assert_eq!("1\nx\n", parts[1].text);
+
+        let err = parts[1].parse(numbers::<u64>).unwrap_err();
+        assert_eq!(
+            ParseError::new(6, "x", "bad value \"x\": invalid digit found in string"),
+            err
+        );
+
+        let rotations = lines("L68\nR48", |l| l[1..].parse::<u32>()).unwrap();
+        assert_eq!(vec![68, 48], rotations);
+    }
+}
@@ -0,0 +1,100 @@
+use std::{error::Error, fmt::Display, str::FromStr};
+
+use crate::value;
+
+/// Failure of a `#[derive(FromLine)]` parser: either no pattern matched, or one field didn't
+/// parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternError {
+    pub field: Option<String>,
+    pub reason: String,
+}
+
+impl PatternError {
+    pub fn mismatch(patterns: &[&str]) -> Self {
+        let patterns = patterns
+            .iter()
+            .map(|p| format!("{p:?}"))
+            .collect::<Vec<_>>();
+        Self {
+            field: None,
+            reason: format!("expected {}", patterns.join(" or ")),
+        }
+    }
+}
+
+impl Display for PatternError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.field {
+            Some(field) => write!(f, "field `{field}`: {}", self.reason),
+            None => write!(f, "{}", self.reason),
+        }
+    }
+}
+
+impl Error for PatternError {}
+
+/// Splits `s` around the literal parts of a pattern, returning the text between them.
+///
+/// Each placeholder stops at the first occurrence of the literal following it, except the last
+/// one which runs up to the final literal.
+pub fn match_pattern<'a>(s: &'a str, literals: &[&str]) -> Option<Vec<&'a str>> {
+    let (first, rest) = literals.split_first()?;
+    let mut s = s.strip_prefix(first)?;
+    let mut caps = Vec::with_capacity(rest.len());
+    for (i, lit) in rest.iter().enumerate() {
+        if i == rest.len() - 1 {
+            caps.push(s.strip_suffix(lit)?);
+            s = "";
+        } else {
+            let (cap, tail) = s.split_once(lit)?;
+            caps.push(cap);
+            s = tail;
+        }
+    }
+
+    s.is_empty().then_some(caps)
+}
+
+/// Parses the text captured for one field.
+pub fn field<T>(name: &str, s: &str) -> Result<T, PatternError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    value(s).map_err(|reason| PatternError {
+        field: Some(name.to_string()),
+        reason,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{PatternError, field, match_pattern};
+
+    #[test]
+    fn test_match() {
+        assert_eq!(
+            Some(vec!["1", "2", "3"]),
+            match_pattern("1,2,3", &["", ",", ",", ""])
+        );
+        assert_eq!(Some(vec!["12"]), match_pattern("L12", &["L", ""]));
+        assert_eq!(None, match_pattern("R12", &["L", ""]));
+        assert_eq!(None, match_pattern("1,2", &["", ",", ",", ""]));
+        assert_eq!(Some(vec![]), match_pattern("on", &["on"]));
+        assert_eq!(None, match_pattern("one", &["on"]));
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = field::<u8>("x", "300").unwrap_err();
+        assert_eq!(
+            "field `x`: bad value \"300\": number too large to fit in target type",
+            err.to_string()
+        );
+        assert_eq!(
+            "expected \"L{0}\" or \"R{0}\"",
+            PatternError::mismatch(&["L{0}", "R{0}"]).to_string()
+        );
+    }
+}
@@ -0,0 +1,178 @@
+//! A global allocator that keeps count of what goes through it, installed by the `count-allocs`
+//! feature.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    fmt::Display,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+static COUNT: AtomicUsize = AtomicUsize::new(0);
+static TOTAL: AtomicUsize = AtomicUsize::new(0);
+
+/// The system allocator, counting allocations and live bytes on the way.
+pub struct Counting;
+
+impl Counting {
+    fn grow(size: usize) {
+        COUNT.fetch_add(1, Ordering::Relaxed);
+        TOTAL.fetch_add(size, Ordering::Relaxed);
+        let current = CURRENT.fetch_add(size, Ordering::Relaxed) + size;
+        PEAK.fetch_max(current, Ordering::Relaxed);
+    }
+
+    fn shrink(size: usize) {
+        CURRENT.fetch_sub(size, Ordering::Relaxed);
+    }
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            Self::grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc_zeroed(layout) };
+        if !ptr.is_null() {
+            Self::grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        Self::shrink(layout.size());
+    }
+
+    /// Counts as a fresh allocation of the new size, with the old one freed.
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new = unsafe { System.realloc(ptr, layout, new_size) };
+        if !new.is_null() {
+            Self::shrink(layout.size());
+            Self::grow(new_size);
+        }
+        new
+    }
+}
+
+/// Whether the counting allocator is the global one; without it every [`Memory`] is empty.
+pub fn is_counting() -> bool {
+    cfg!(feature = "count-allocs")
+}
+
+/// What a phase allocated. The counters are shared by the whole process, so they are only exact
+/// when nothing else runs at the same time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Memory {
+    /// Highest number of bytes live at once, over what was live when the phase started.
+    pub peak: usize,
+    pub allocations: usize,
+    /// Bytes allocated over the whole phase, freed or not.
+    pub allocated: usize,
+}
+
+/// The counters at the start of a phase.
+pub(crate) struct Mark {
+    current: usize,
+    count: usize,
+    total: usize,
+}
+
+impl Mark {
+    pub(crate) fn new() -> Self {
+        let current = CURRENT.load(Ordering::Relaxed);
+        PEAK.store(current, Ordering::Relaxed);
+        Self {
+            current,
+            count: COUNT.load(Ordering::Relaxed),
+            total: TOTAL.load(Ordering::Relaxed),
+        }
+    }
+
+    pub(crate) fn memory(&self) -> Memory {
+        Memory {
+            peak: PEAK.load(Ordering::Relaxed).saturating_sub(self.current),
+            allocations: COUNT.load(Ordering::Relaxed) - self.count,
+            allocated: TOTAL.load(Ordering::Relaxed) - self.total,
+        }
+    }
+}
+
+struct Bytes(usize);
+
+impl Display for Bytes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+        let mut size = self.0 as f64;
+        let mut unit = 0;
+        while size >= 1024.0 && unit < UNITS.len() - 1 {
+            size /= 1024.0;
+            unit += 1;
+        }
+        if unit == 0 {
+            write!(f, "{} B", self.0)
+        } else {
+            write!(f, "{size:.1} {}", UNITS[unit])
+        }
+    }
+}
+
+impl Display for Memory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "peak {:>10}  {:>8} allocs  {:>10} total",
+            Bytes(self.peak).to_string(),
+            self.allocations,
+            Bytes(self.allocated).to_string()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::alloc::Bytes;
+
+    #[test]
+    #[cfg(not(feature = "count-allocs"))]
+    fn test_counting() {
+        use std::alloc::{GlobalAlloc, Layout};
+
+        use crate::alloc::{Counting, Mark, Memory};
+
+        // Not the global allocator under test, so only these calls move the counters
+        let mark = Mark::new();
+        let small = Layout::from_size_align(100, 8).unwrap();
+        let large = Layout::from_size_align(1000, 8).unwrap();
+        unsafe {
+            let a = Counting.alloc(small);
+            let b = Counting.alloc_zeroed(large);
+            Counting.dealloc(b, large);
+            let a = Counting.realloc(a, small, 300);
+            Counting.dealloc(a, Layout::from_size_align(300, 8).unwrap());
+        }
+
+        assert_eq!(
+            Memory {
+                peak: 1100,
+                allocations: 3,
+                allocated: 1400,
+            },
+            mark.memory()
+        );
+        assert_eq!(Memory::default(), Mark::new().memory());
+    }
+
+    #[test]
+    fn test_bytes() {
+        assert_eq!("512 B", Bytes(512).to_string());
+        assert_eq!("1.5 KiB", Bytes(1536).to_string());
+        assert_eq!("3.0 MiB", Bytes(3 << 20).to_string());
+    }
+}
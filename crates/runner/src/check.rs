@@ -0,0 +1,213 @@
+use std::fmt::Display;
+
+use crate::{Day, Rng};
+
+/// What a solver made of an input: its answers, or the error it failed with.
+type Outcome = Result<Vec<Option<String>>, String>;
+
+/// A generated input on which a day and its reference disagree, shrunk as far as it would go.
+#[derive(Debug, Clone)]
+pub struct Mismatch {
+    pub day: u8,
+    pub seed: u64,
+    pub size: usize,
+    pub input: String,
+    pub fast: Result<Vec<Option<String>>, String>,
+    pub reference: Result<Vec<Option<String>>, String>,
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Day {:02} disagrees with its reference (seed {}, size {})",
+            self.day, self.seed, self.size
+        )?;
+        writeln!(f, "  solution:  {:?}", self.fast)?;
+        writeln!(f, "  reference: {:?}", self.reference)?;
+        write!(f, "Input:\n{}", self.input)
+    }
+}
+
+fn outcome(report: anyhow::Result<crate::Report>) -> Outcome {
+    report
+        .map(|r| r.phases.into_iter().map(|p| p.answer).collect())
+        .map_err(|e| e.to_string())
+}
+
+/// Runs both solvers on `input`, returning their outcomes if they disagree.
+///
+/// Two failures count as agreement, whatever the messages say.
+fn compare(day: &Day, input: &str) -> Option<(Outcome, Outcome)> {
+    let reference = outcome(day.solve_reference(input)?);
+    let fast = outcome(day.solve(input));
+    match (&fast, &reference) {
+        (Err(_), Err(_)) => None,
+        (a, b) if a == b => None,
+        _ => Some((fast, reference)),
+    }
+}
+
+/// Checks a day against its reference on `cases` generated inputs of up to `max_size` items.
+///
+/// Does nothing for days without both a generator and a reference. The first mismatch found is
+/// shrunk by first trying smaller sizes with the same seed, then dropping lines one at a time
+/// for as long as the two solvers still disagree.
+pub fn differential(day: &Day, cases: u64, max_size: usize) -> Result<(), Box<Mismatch>> {
+    let max_size = max_size.max(1);
+    for seed in 0..cases {
+        let size = 1 + seed as usize % max_size;
+        let Some(input) = day.generate(&mut Rng::new(seed), size) else {
+            return Ok(());
+        };
+        if compare(day, &input).is_none() {
+            continue;
+        }
+
+        let (size, input) = (1..size)
+            .filter_map(|size| {
+                let input = day.generate(&mut Rng::new(seed), size)?;
+                compare(day, &input).map(|_| (size, input))
+            })
+            .next()
+            .unwrap_or((size, input));
+        let input = drop_lines(day, input);
+        let (fast, reference) = compare(day, &input).expect("A shrunk input should still fail");
+        return Err(Box::new(Mismatch {
+            day: day.number,
+            seed,
+            size,
+            input,
+            fast,
+            reference,
+        }));
+    }
+    Ok(())
+}
+
+/// Removes single lines from a failing input while it keeps failing.
+fn drop_lines(day: &Day, mut input: String) -> String {
+    let mut i = 0;
+    loop {
+        let lines = input.split_inclusive('\n').collect::<Vec<_>>();
+        if i >= lines.len() {
+            return input;
+        }
+        let candidate = lines
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .map(|(_, l)| *l)
+            .collect::<String>();
+        if compare(day, &candidate).is_some() {
+            input = candidate;
+        } else {
+            i += 1;
+        }
+    }
+}
+
+/// The tests every day shares, to expand inside its test module:
+///
+/// - `test_generate`: the generator is reproducible from its seed, and the day solves what it
+///   makes at `size` and at 0;
+/// - `test_reference`: with `reference: (cases, max_size)`, [`differential`] finds no mismatch;
+/// - `test_solve_never_panics` and `test_solve_near_valid_never_panics`: solving any text, or
+///   text matching the `near_valid` regex, returns instead of panicking.
+///
+/// The calling crate needs `proptest` among its dev-dependencies.
+#[macro_export]
+macro_rules! standard_tests {
+    (
+        $day:path,
+        size: $size:expr,
+        $(reference: ($cases:expr, $max_size:expr),)?
+        near_valid: $near_valid:literal $(,)?
+    ) => {
+        #[test]
+        fn test_generate() {
+            let day = $day();
+            for size in [$size, 0] {
+                let input = day
+                    .generate(&mut $crate::Rng::new(1), size)
+                    .expect("The day should have a generator");
+                assert_eq!(Some(&input), day.generate(&mut $crate::Rng::new(1), size).as_ref());
+                day.solve(&input).unwrap();
+            }
+        }
+
+        $(
+            #[test]
+            fn test_reference() {
+                if let Err(mismatch) = $crate::differential(&$day(), $cases, $max_size) {
+                    panic!("{mismatch}");
+                }
+            }
+        )?
+
+        ::proptest::proptest! {
+            #[test]
+            fn test_solve_never_panics(s in "\\PC*") {
+                let _ = $day().solve(&s);
+            }
+
+            #[test]
+            fn test_solve_near_valid_never_panics(s in $near_valid) {
+                let _ = $day().solve(&s);
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::{Day, differential};
+
+    type Input = Vec<u64>;
+
+    fn parse(s: &str) -> Result<Input> {
+        s.lines().map(|l| Ok(l.parse()?)).collect()
+    }
+
+    fn sum(input: &Input) -> u64 {
+        input.iter().sum()
+    }
+
+    /// Wrong as soon as a number over 50 shows up.
+    fn capped_sum(input: &Input) -> u64 {
+        input.iter().map(|n| *n.min(&50)).sum()
+    }
+
+    fn generate(rng: &mut crate::Rng, size: usize) -> String {
+        (0..size).map(|_| format!("{}\n", rng.below(60))).collect()
+    }
+
+    #[test]
+    fn test_agreement() {
+        let day = Day::new(1, "", parse, sum, sum)
+            .with_generator(generate)
+            .with_reference(parse, sum, sum);
+        assert!(differential(&day, 50, 20).is_ok());
+
+        let no_reference = Day::new(1, "", parse, capped_sum, sum).with_generator(generate);
+        assert!(differential(&no_reference, 50, 20).is_ok());
+    }
+
+    #[test]
+    fn test_shrinking() {
+        let day = Day::new(2, "", parse, capped_sum, sum)
+            .with_generator(generate)
+            .with_reference(parse, sum, sum);
+        let mismatch = differential(&day, 50, 20).unwrap_err();
+        assert_eq!(1, mismatch.input.lines().count());
+        let n = mismatch.input.trim().parse::<u64>().unwrap();
+        assert!(n > 50);
+        assert_eq!(
+            Ok(vec![None, Some("50".to_string()), Some(n.to_string())]),
+            mismatch.fast
+        );
+        assert!(mismatch.to_string().starts_with("Day 02 disagrees"));
+    }
+}
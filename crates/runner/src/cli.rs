@@ -0,0 +1,232 @@
+use std::{
+    io::Read,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Result, anyhow};
+
+use crate::{Day, Rng, is_counting, par};
+
+const USAGE: &str = "usage: aoc run <day|all> [--input <file|->] [--sequential]
+       aoc gen <day> [--size <n>] [--seed <n>]";
+
+/// Which days to run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Selection {
+    All,
+    Day(u8),
+}
+
+impl Selection {
+    fn parse(s: &str) -> Result<Self> {
+        match s {
+            "all" => Ok(Self::All),
+            n => Ok(Self::Day(n.parse().map_err(|_| anyhow!("Bad day {n:?}"))?)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Run {
+        days: Selection,
+        input: Option<String>,
+        /// Solve one day at a time on a single thread, so that timings are comparable.
+        sequential: bool,
+    },
+    Gen {
+        day: u8,
+        size: usize,
+        seed: u64,
+    },
+}
+
+impl Command {
+    /// Parses the arguments following the program name.
+    pub fn parse(args: &[String]) -> Result<Self> {
+        let Some((command, rest)) = args.split_first() else {
+            return Err(anyhow!(USAGE));
+        };
+        let Some((days, flags)) = rest.split_first() else {
+            return Err(anyhow!(USAGE));
+        };
+        // The only flag without a value
+        let sequential = flags.iter().any(|f| f == "--sequential");
+        let flags = flags
+            .iter()
+            .filter(|f| *f != "--sequential")
+            .cloned()
+            .collect::<Vec<_>>();
+        if sequential && command != "run" {
+            return Err(anyhow!(
+                "Unknown flag \"--sequential\" for {command}\n{USAGE}"
+            ));
+        }
+        if flags.len() % 2 != 0 {
+            return Err(anyhow!(
+                "Missing value for {}\n{USAGE}",
+                flags[flags.len() - 1]
+            ));
+        }
+        let flag = |name: &str| {
+            flags
+                .chunks(2)
+                .find(|pair| pair[0] == name)
+                .map(|pair| pair[1].as_str())
+        };
+        let allowed: &[&str] = match command.as_str() {
+            "run" => &["--input"],
+            "gen" => &["--size", "--seed"],
+            _ => return Err(anyhow!("Unknown command {command:?}\n{USAGE}")),
+        };
+        if let Some(pair) = flags.chunks(2).find(|p| !allowed.contains(&p[0].as_str())) {
+            return Err(anyhow!("Unknown flag {:?} for {command}\n{USAGE}", pair[0]));
+        }
+
+        let days = Selection::parse(days)?;
+        if command == "run" {
+            let input = flag("--input").map(str::to_string);
+            if input.is_some() && days == Selection::All {
+                return Err(anyhow!("--input needs a single day"));
+            }
+            return Ok(Self::Run {
+                days,
+                input,
+                sequential,
+            });
+        }
+
+        let Selection::Day(day) = days else {
+            return Err(anyhow!("gen needs a single day"));
+        };
+        Ok(Self::Gen {
+            day,
+            size: flag("--size").unwrap_or("100").parse()?,
+            seed: flag("--seed").unwrap_or("0").parse()?,
+        })
+    }
+}
+
+fn find(days: &[Day], number: u8) -> Result<&Day> {
+    days.iter()
+        .find(|d| d.number == number)
+        .ok_or_else(|| anyhow!("No day {number}"))
+}
+
+fn read_input(path: &str) -> Result<String> {
+    if path == "-" {
+        let mut input = String::new();
+        std::io::stdin().read_to_string(&mut input)?;
+        return Ok(input);
+    }
+    Ok(std::fs::read_to_string(path)?)
+}
+
+/// Entry point of the `aoc` binary.
+pub fn cli(days: &[Day]) -> Result<()> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match Command::parse(&args)? {
+        Command::Run {
+            days: which,
+            input,
+            sequential,
+        } => {
+            // The allocation counters are shared, so they need one phase at a time
+            par::set_sequential(sequential || is_counting());
+            let selected = match which {
+                Selection::All => days.iter().collect::<Vec<_>>(),
+                Selection::Day(n) => vec![find(days, n)?],
+            };
+            let input = input.as_deref().map(read_input).transpose()?;
+
+            let start = Instant::now();
+            let reports = par::map(&selected, |day| {
+                day.solve(input.as_deref().unwrap_or(day.input))
+            })
+            .into_iter()
+            .collect::<Result<Vec<_>>>()?;
+            let wall = start.elapsed();
+
+            for report in reports.iter() {
+                print!("{report}");
+            }
+            if reports.len() > 1 {
+                let total = reports.iter().map(|r| r.total()).sum::<Duration>();
+                println!("Total {total:.3?}, {wall:.3?} wall clock");
+            }
+        }
+        Command::Gen { day, size, seed } => {
+            let input = find(days, day)?
+                .generate(&mut Rng::new(seed), size)
+                .ok_or_else(|| anyhow!("Day {day} has no generator"))?;
+            print!("{input}");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Command, Selection};
+
+    fn parse(args: &str) -> anyhow::Result<Command> {
+        Command::parse(
+            &args
+                .split_whitespace()
+                .map(str::to_string)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Command::Gen {
+                day: 9,
+                size: 5000,
+                seed: 1
+            },
+            parse("gen 9 --size 5000 --seed 1").unwrap()
+        );
+        assert_eq!(
+            Command::Gen {
+                day: 2,
+                size: 100,
+                seed: 0
+            },
+            parse("gen 2").unwrap()
+        );
+        assert_eq!(
+            Command::Run {
+                days: Selection::All,
+                input: None,
+                sequential: false
+            },
+            parse("run all").unwrap()
+        );
+        assert_eq!(
+            Command::Run {
+                days: Selection::All,
+                input: None,
+                sequential: true
+            },
+            parse("run all --sequential").unwrap()
+        );
+        assert_eq!(
+            Command::Run {
+                days: Selection::Day(4),
+                input: Some("-".to_string()),
+                sequential: true
+            },
+            parse("run 4 --sequential --input -").unwrap()
+        );
+
+        assert!(parse("").is_err());
+        assert!(parse("gen all").is_err());
+        assert!(parse("gen 9 --size").is_err());
+        assert!(parse("gen 9 --input x").is_err());
+        assert!(parse("run all --input x").is_err());
+        assert!(parse("gen 9 --sequential").is_err());
+        assert!(parse("fly 9").is_err());
+    }
+}
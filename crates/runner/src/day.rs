@@ -0,0 +1,252 @@
+use std::{
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+
+use crate::{
+    Memory, Rng,
+    alloc::{Mark, is_counting},
+};
+
+/// Anything a part can return: a number, or a number that may fail to compute.
+pub trait Answer {
+    fn answer(self) -> Result<String>;
+}
+
+macro_rules! answer {
+    ($($t:ty),*) => {
+        $(impl Answer for $t {
+            fn answer(self) -> Result<String> {
+                Ok(self.to_string())
+            }
+        })*
+    };
+}
+
+answer!(u32, u64, u128, usize, i32, i64, String);
+
+impl<T> Answer for Result<T>
+where
+    T: Display,
+{
+    fn answer(self) -> Result<String> {
+        self.map(|v| v.to_string())
+    }
+}
+
+/// One step of solving a day, with its answer if it has one.
+#[derive(Debug, Clone)]
+pub struct Phase {
+    pub name: &'static str,
+    pub answer: Option<String>,
+    pub elapsed: Duration,
+    /// Only counted with the `count-allocs` feature.
+    pub memory: Option<Memory>,
+}
+
+impl Phase {
+    fn measure<T, F>(name: &'static str, f: F) -> (T, Self)
+    where
+        F: FnOnce() -> T,
+    {
+        let mark = is_counting().then(Mark::new);
+        let start = Instant::now();
+        let res = f();
+        let phase = Self {
+            name,
+            answer: None,
+            elapsed: start.elapsed(),
+            memory: mark.map(|m| m.memory()),
+        };
+        (res, phase)
+    }
+}
+
+/// The phases of a solved day, in order: parsing, then each part.
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub day: u8,
+    pub phases: Vec<Phase>,
+}
+
+impl Report {
+    pub fn total(&self) -> Duration {
+        self.phases.iter().map(|p| p.elapsed).sum()
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Day {:02}", self.day)?;
+        for phase in self.phases.iter() {
+            write!(
+                f,
+                "  {:<8} {:>20} {:>12.3?}",
+                phase.name,
+                phase.answer.as_deref().unwrap_or(""),
+                phase.elapsed
+            )?;
+            match &phase.memory {
+                Some(memory) => writeln!(f, "  {memory}")?,
+                None => writeln!(f)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+type Solver = Box<dyn Fn(&str) -> Result<Report> + Send + Sync>;
+
+/// Produces an input of roughly `size` items.
+pub type Generator = fn(&mut Rng, usize) -> String;
+
+/// A day's solution with its types erased, so that the runner can treat every day the same.
+pub struct Day {
+    pub number: u8,
+    /// The puzzle input bundled with the binary.
+    pub input: &'static str,
+    solve: Solver,
+    reference: Option<Solver>,
+    generator: Option<Generator>,
+}
+
+fn solver<I, P, A, B, PA, PB>(number: u8, parse: P, part_01: PA, part_02: PB) -> Solver
+where
+    P: Fn(&str) -> Result<I> + Send + Sync + 'static,
+    PA: Fn(&I) -> A + Send + Sync + 'static,
+    PB: Fn(&I) -> B + Send + Sync + 'static,
+    A: Answer,
+    B: Answer,
+{
+    Box::new(move |text: &str| {
+        let (input, parsed) = Phase::measure("parse", || parse(text));
+        let input = input?;
+
+        let mut phases = vec![parsed];
+        let (answer, mut phase) = Phase::measure("Part 01", || part_01(&input));
+        phase.answer = Some(answer.answer()?);
+        phases.push(phase);
+        let (answer, mut phase) = Phase::measure("Part 02", || part_02(&input));
+        phase.answer = Some(answer.answer()?);
+        phases.push(phase);
+
+        Ok(Report {
+            day: number,
+            phases,
+        })
+    })
+}
+
+impl Day {
+    pub fn new<I, P, A, B, PA, PB>(
+        number: u8,
+        input: &'static str,
+        parse: P,
+        part_01: PA,
+        part_02: PB,
+    ) -> Self
+    where
+        P: Fn(&str) -> Result<I> + Send + Sync + 'static,
+        PA: Fn(&I) -> A + Send + Sync + 'static,
+        PB: Fn(&I) -> B + Send + Sync + 'static,
+        A: Answer,
+        B: Answer,
+    {
+        Self {
+            number,
+            input,
+            solve: solver(number, parse, part_01, part_02),
+            reference: None,
+            generator: None,
+        }
+    }
+
+    /// A slow but obviously correct solution, to check the real one against.
+    pub fn with_reference<I, P, A, B, PA, PB>(mut self, parse: P, part_01: PA, part_02: PB) -> Self
+    where
+        P: Fn(&str) -> Result<I> + Send + Sync + 'static,
+        PA: Fn(&I) -> A + Send + Sync + 'static,
+        PB: Fn(&I) -> B + Send + Sync + 'static,
+        A: Answer,
+        B: Answer,
+    {
+        self.reference = Some(solver(self.number, parse, part_01, part_02));
+        self
+    }
+
+    pub fn with_generator(mut self, generator: Generator) -> Self {
+        self.generator = Some(generator);
+        self
+    }
+
+    pub fn solve(&self, input: &str) -> Result<Report> {
+        (self.solve)(input)
+    }
+
+    pub fn solve_reference(&self, input: &str) -> Option<Result<Report>> {
+        self.reference.as_ref().map(|r| r(input))
+    }
+
+    pub fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        self.generator.map(|g| g(rng, size))
+    }
+
+    /// What a day's own binary does: solve the bundled input and print both answers.
+    pub fn main(&self) -> Result<()> {
+        let report = self.solve(self.input)?;
+        for phase in report.phases.iter() {
+            if let Some(answer) = &phase.answer {
+                println!("{}: {answer}", phase.name);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::{Result, anyhow};
+
+    use crate::{Day, Rng};
+
+    type Input = Vec<u64>;
+
+    fn parse(s: &str) -> Result<Input> {
+        s.lines().map(|l| Ok(l.parse()?)).collect()
+    }
+
+    fn sum(input: &Input) -> u64 {
+        input.iter().sum()
+    }
+
+    fn max(input: &Input) -> Result<u64> {
+        input
+            .iter()
+            .max()
+            .copied()
+            .ok_or_else(|| anyhow!("No numbers"))
+    }
+
+    #[test]
+    fn test_solve() {
+        let day = Day::new(3, "1\n2\n3", parse, sum, max)
+            .with_generator(|rng, size| (0..size).map(|_| format!("{}\n", rng.below(9))).collect());
+
+        let report = day.solve(day.input).unwrap();
+        assert_eq!(3, report.day);
+        let names = report.phases.iter().map(|p| p.name).collect::<Vec<_>>();
+        assert_eq!(vec!["parse", "Part 01", "Part 02"], names);
+        assert_eq!(Some("6"), report.phases[1].answer.as_deref());
+        assert_eq!(Some("3"), report.phases[2].answer.as_deref());
+        assert!(report.to_string().starts_with("Day 03\n"));
+
+        assert!(day.solve("").is_err());
+        assert!(day.solve("x").is_err());
+
+        let generated = day.generate(&mut Rng::new(1), 10).unwrap();
+        assert_eq!(generated, day.generate(&mut Rng::new(1), 10).unwrap());
+        assert_eq!(10, generated.lines().count());
+    }
+}
@@ -0,0 +1,16 @@
+mod alloc;
+mod check;
+mod cli;
+mod day;
+pub mod par;
+mod rng;
+
+pub use alloc::{Counting, Memory, is_counting};
+pub use check::{Mismatch, differential};
+pub use cli::{Command, Selection, cli};
+pub use day::{Answer, Day, Generator, Phase, Report};
+pub use rng::Rng;
+
+#[cfg(feature = "count-allocs")]
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
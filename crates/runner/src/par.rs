@@ -0,0 +1,152 @@
+//! Splitting independent work over threads, unless the runner was asked to stay sequential.
+
+use std::{
+    cell::Cell,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    thread,
+};
+
+static SEQUENTIAL: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// Set on the threads [`spread`] starts, whose work is already spread out.
+    static WORKER: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Forces everything onto the calling thread, for timings that don't depend on the other work.
+pub fn set_sequential(sequential: bool) {
+    SEQUENTIAL.store(sequential, Ordering::Relaxed);
+}
+
+pub fn is_sequential() -> bool {
+    SEQUENTIAL.load(Ordering::Relaxed)
+}
+
+fn threads() -> usize {
+    if is_sequential() {
+        return 1;
+    }
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// Runs `f` on `0..n`, returning the results in order.
+fn run<R, F>(n: usize, f: F) -> Vec<R>
+where
+    R: Send,
+    F: Fn(usize) -> R + Sync,
+{
+    spread(threads(), n, f)
+}
+
+/// Each thread takes the next index as soon as it is free, so uneven work still spreads out.
+///
+/// Called from one of its own workers, it stays on that thread: the other workers already keep
+/// every core busy.
+fn spread<R, F>(threads: usize, n: usize, f: F) -> Vec<R>
+where
+    R: Send,
+    F: Fn(usize) -> R + Sync,
+{
+    let threads = threads.min(n);
+    if threads <= 1 || WORKER.get() {
+        return (0..n).map(f).collect();
+    }
+
+    let next = AtomicUsize::new(0);
+    let mut done = thread::scope(|s| {
+        let workers = (0..threads)
+            .map(|_| {
+                s.spawn(|| {
+                    WORKER.set(true);
+                    let mut out = vec![];
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        if i >= n {
+                            return out;
+                        }
+                        out.push((i, f(i)));
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        workers
+            .into_iter()
+            .flat_map(|w| w.join().unwrap_or_else(|e| std::panic::resume_unwind(e)))
+            .collect::<Vec<_>>()
+    });
+    done.sort_by_key(|(i, _)| *i);
+    done.into_iter().map(|(_, r)| r).collect()
+}
+
+/// `f` applied to every item, spread over threads.
+pub fn map<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    run(items.len(), |i| f(&items[i]))
+}
+
+/// Splits a loop over every pair of items: `f` gets each item with the ones after it, and its
+/// results come back in the order of the items.
+pub fn pairs<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T, &[T]) -> R + Sync,
+{
+    run(items.len(), |i| f(&items[i], &items[i + 1..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use crate::par::{map, pairs, spread};
+
+    #[test]
+    fn test_map() {
+        let items = (0..100u64).collect::<Vec<_>>();
+        assert_eq!(
+            items.iter().map(|i| i * i).collect::<Vec<_>>(),
+            map(&items, |i| i * i)
+        );
+        assert!(map(&[] as &[u64], |i| *i).is_empty());
+    }
+
+    #[test]
+    fn test_spread() {
+        let squares = spread(4, 1000, |i| i * i);
+        assert_eq!((0..1000).map(|i| i * i).collect::<Vec<_>>(), squares);
+        assert_eq!(vec![0], spread(4, 1, |i| i));
+    }
+
+    #[test]
+    fn test_spread_nested() {
+        let inline = spread(4, 8, |_| {
+            let worker = thread::current().id();
+            spread(4, 8, |_| thread::current().id())
+                .into_iter()
+                .all(|id| id == worker)
+        });
+        assert_eq!(vec![true; 8], inline);
+        assert_eq!(
+            vec![vec![0, 0, 0], vec![0, 1, 2]],
+            spread(2, 2, |i| spread(4, 3, |j| i * j))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "seven")]
+    fn test_spread_panics() {
+        spread(4, 10, |i| assert_ne!(7, i, "seven"));
+    }
+
+    #[test]
+    fn test_pairs() {
+        let items = [1, 2, 3, 4];
+        let sums = pairs(&items, |a, rest| rest.iter().map(|b| a * b).sum::<u32>());
+        assert_eq!(vec![9, 14, 12, 0], sums);
+    }
+}
@@ -0,0 +1,95 @@
+use std::ops::RangeInclusive;
+
+/// A small seeded generator (SplitMix64), so that generated inputs can be reproduced from their
+/// seed alone.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`, or 0 if `n` is 0.
+    pub fn below(&mut self, n: u64) -> u64 {
+        ((self.next_u64() as u128 * n as u128) >> 64) as u64
+    }
+
+    /// A number in `range`, which must not be empty.
+    pub fn range(&mut self, range: RangeInclusive<u64>) -> u64 {
+        let (lo, hi) = (*range.start(), *range.end());
+        assert!(
+            lo <= hi,
+            "Cannot pick a number in the empty range {lo}..={hi}"
+        );
+        if hi - lo == u64::MAX {
+            return self.next_u64();
+        }
+        lo + self.below(hi - lo + 1)
+    }
+
+    /// True with probability `p`: never for 0, always for 1.
+    pub fn chance(&mut self, p: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < p
+    }
+
+    /// One of `items`, which must not be empty.
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        assert!(!items.is_empty(), "Cannot pick from no items");
+        &items[self.below(items.len() as u64) as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Rng;
+
+    #[test]
+    fn test_seeded() {
+        let a = (0..5).map(|_| Rng::new(1).next_u64()).collect::<Vec<_>>();
+        assert!(a.windows(2).all(|w| w[0] == w[1]));
+
+        let mut rng = Rng::new(7);
+        let mut other = Rng::new(8);
+        assert_ne!(rng.next_u64(), other.next_u64());
+
+        for _ in 0..1000 {
+            assert!((3..=5).contains(&rng.range(3..=5)));
+            assert!(rng.below(10) < 10);
+        }
+        assert_eq!(0, rng.below(0));
+        assert!(!rng.chance(-1.0));
+        assert!(rng.chance(1.0));
+        assert_eq!(u64::MAX, rng.range(u64::MAX..=u64::MAX));
+    }
+
+    #[test]
+    fn test_never() {
+        // A state whose first output is 0, the one value `<=` let through
+        let mut rng = Rng::new(0u64.wrapping_sub(0x9e3779b97f4a7c15));
+        assert!(!rng.chance(0.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "empty range 5..=1")]
+    fn test_reversed_range() {
+        #[allow(clippy::reversed_empty_ranges)]
+        Rng::new(1).range(5..=1);
+    }
+
+    #[test]
+    #[should_panic(expected = "no items")]
+    fn test_pick_nothing() {
+        Rng::new(1).pick::<u8>(&[]);
+    }
+}
@@ -1,25 +1,39 @@
-use anyhow::anyhow;
-use std::str::FromStr;
+use grid::{Direction, Grid, Topology};
+use grid_derive::FromLine;
+use runner::{Day, Rng};
+
+pub fn day() -> Day {
+    Day::new(
+        1,
+        include_str!("../inputs/01/input.txt"),
+        parse_input,
+        |input| part_one(input),
+        |input| part_two(input),
+    )
+    .with_generator(generate)
+}
 
 fn main() -> anyhow::Result<()> {
-    let input = include_str!("../inputs/01/input.txt");
-    let input = parse_input(input)?;
-    let part1 = part_one(&input);
-    println!("Part one: {}", part1);
-    let part2 = part_two(&input);
-    println!("Part one: {}", part2);
-    Ok(())
+    day().main()
 }
 
 fn parse_input(input: &str) -> anyhow::Result<Vec<Rotation>> {
-    input.lines().map(|l| l.parse::<Rotation>()).collect()
+    Ok(parse::lines(input, str::parse::<Rotation>)?)
+}
+
+/// The dial's hundred positions, as a single row that wraps around.
+fn dial() -> Grid<usize> {
+    let mut dial = Grid::with_capacity(1);
+    dial.add_row((0..100).collect());
+    dial.with_topology(Topology::WrapRows)
 }
 
 fn part_one(input: &[Rotation]) -> usize {
+    let dial = dial();
     input
         .iter()
         .fold((50, 0), |(pos, zeroes), rotation| {
-            let pos = rotation.rotate(pos);
+            let pos = rotation.rotate(&dial, pos);
             let zeroes = if pos == 0 { zeroes + 1 } else { zeroes };
             (pos, zeroes)
         })
@@ -27,13 +41,16 @@ fn part_one(input: &[Rotation]) -> usize {
 }
 
 fn part_two(input: &[Rotation]) -> usize {
+    let dial = dial();
     input
         .iter()
         .fold((50, 0), |(mut pos, mut zeroes), rotation| {
-            let mut result = (pos, zeroes);
             let (clicks, rotation) = rotation.ones();
-            for _i in 0..clicks {
-                pos = rotation.rotate(pos);
+            // Every whole turn passes zero exactly once
+            zeroes += clicks / dial.col_count();
+            let mut result = (pos, zeroes);
+            for _i in 0..clicks % dial.col_count() {
+                pos = rotation.rotate(&dial, pos);
                 zeroes = if pos == 0 { zeroes + 1 } else { zeroes };
                 result = (pos, zeroes);
             }
@@ -42,17 +59,24 @@ fn part_two(input: &[Rotation]) -> usize {
         .1
 }
 
+#[derive(FromLine, Debug, PartialEq, Eq)]
 enum Rotation {
+    #[pattern("L{0}")]
     Left(usize),
+    #[pattern("R{0}")]
     Right(usize),
 }
 
 impl Rotation {
-    fn rotate(&self, from: usize) -> usize {
-        match self {
-            Self::Left(clicks) => (100 + from - (clicks % 100)) % 100,
-            Self::Right(clicks) => (from + (clicks % 100)) % 100,
-        }
+    fn rotate(&self, dial: &Grid<usize>, from: usize) -> usize {
+        let (dir, clicks) = match self {
+            Self::Left(clicks) => (Direction::West, *clicks),
+            Self::Right(clicks) => (Direction::East, *clicks),
+        };
+        let (_, to) = dial
+            .step_by(0, from, dir, clicks)
+            .expect("The dial wraps around");
+        *dial.get(0, to).unwrap().val
     }
 
     fn ones(&self) -> (usize, Self) {
@@ -63,20 +87,11 @@ impl Rotation {
     }
 }
 
-impl FromStr for Rotation {
-    type Err = anyhow::Error;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Some(rest) = s.strip_prefix('L') {
-            return Ok(Self::Left(rest.parse::<usize>()?));
-        }
-
-        if let Some(rest) = s.strip_prefix('R') {
-            return Ok(Self::Right(rest.parse::<usize>()?));
-        }
-
-        Err(anyhow!("Can't parse {s}"))
-    }
+/// `size` rotations of up to 999 clicks each.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| format!("{}{}\n", rng.pick(&['L', 'R']), rng.range(1..=999)))
+        .collect()
 }
 
 #[cfg(test)]
@@ -84,10 +99,39 @@ mod tests {
     use super::*;
     #[test]
     fn test_rotation() {
-        assert_eq!(1, Rotation::Right(1).rotate(0));
-        assert_eq!(0, Rotation::Right(1).rotate(99));
+        let dial = dial();
+        assert_eq!(1, Rotation::Right(1).rotate(&dial, 0));
+        assert_eq!(0, Rotation::Right(1).rotate(&dial, 99));
 
-        assert_eq!(0, Rotation::Left(1).rotate(1));
-        assert_eq!(99, Rotation::Left(1).rotate(0));
+        assert_eq!(0, Rotation::Left(1).rotate(&dial, 1));
+        assert_eq!(99, Rotation::Left(1).rotate(&dial, 0));
+        assert_eq!(50, Rotation::Left(250).rotate(&dial, 0));
+        assert_eq!(15, Rotation::Right(usize::MAX).rotate(&dial, 0));
+    }
+
+    #[test]
+    fn test_whole_turns() {
+        assert_eq!(3, part_two(&[Rotation::Left(300)]));
+        assert_eq!(4, part_two(&[Rotation::Right(350)]));
+        assert_eq!(usize::MAX / 100, part_two(&[Rotation::Right(usize::MAX)]));
     }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Rotation::Left(68), "L68".parse().unwrap());
+        assert_eq!(Rotation::Right(5), "R5".parse().unwrap());
+
+        let err = parse_input("L68\nRx\n").unwrap_err();
+        assert_eq!(
+            "line 2: field `0`: bad value \"x\": invalid digit found in string in \"Rx\"",
+            err.to_string()
+        );
+        assert!(parse_input("U3").is_err());
+    }
+
+    runner::standard_tests!(
+        day,
+        size: 50,
+        near_valid: "([LR][0-9]{0,4}\\n?){0,10}",
+    );
 }
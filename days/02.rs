@@ -1,34 +1,43 @@
 use std::ops::Range;
 
-use anyhow::anyhow;
+use runner::{Day, Rng};
+
+pub fn day() -> Day {
+    Day::new(
+        2,
+        include_str!("../inputs/02/input.txt"),
+        parse_input,
+        |input| part_01(input),
+        |input| part_02(input),
+    )
+    .with_generator(generate)
+    .with_reference(
+        parse_input,
+        |input| naive_01(input),
+        |input| naive_02(input),
+    )
+}
 
 fn main() -> anyhow::Result<()> {
-    let input = parse_input(include_str!("../inputs/02/input.txt"))?;
-
-    let part1 = part_01(&input);
-    println!("Part 01: {part1}");
-    let part2 = part_02(&input);
-    println!("Part 02: {part2}");
-    Ok(())
+    day().main()
 }
 
-fn resolver<F>(input: &Vec<Range<u64>>, validator: F) -> u64
+fn resolver<F>(input: &[Range<u64>], validator: F) -> u64
 where
-    F: Fn(&u64) -> bool,
+    F: Fn(&u64) -> bool + Sync,
 {
-    input
-        .iter()
-        .map(|range| range.clone().filter(&validator).sum::<u64>())
+    runner::par::map(input, |range| range.clone().filter(&validator).sum::<u64>())
+        .into_iter()
         .sum()
 }
 
-fn part_01(input: &Vec<Range<u64>>) -> u64 {
+fn part_01(input: &[Range<u64>]) -> u64 {
     resolver(input, invalid_id_part1)
 }
 
 fn invalid_id_part1(n: &u64) -> bool {
     let count = count_digits(*n);
-    if count % 2 == 1 {
+    if !count.is_multiple_of(2) {
         return false;
     }
 
@@ -36,7 +45,7 @@ fn invalid_id_part1(n: &u64) -> bool {
     n / tens == n % tens
 }
 
-fn part_02(input: &Vec<Range<u64>>) -> u64 {
+fn part_02(input: &[Range<u64>]) -> u64 {
     resolver(input, invalid_id_part2)
 }
 
@@ -46,10 +55,10 @@ fn invalid_id_part2(n: &u64) -> bool {
         return false;
     }
     for group in (1..=digits / 2).rev() {
-        if digits % group != 0 {
+        if !digits.is_multiple_of(group) {
             continue;
         }
-        let mut groups = GroupsOf::new(*n, group as u32).into_iter();
+        let mut groups = GroupsOf::new(*n, group as u32);
         let Some(pivot) = groups.next() else {
             return false;
         };
@@ -62,20 +71,10 @@ fn invalid_id_part2(n: &u64) -> bool {
 }
 
 fn parse_input(input: &str) -> anyhow::Result<Vec<Range<u64>>> {
-    input
-        .lines()
-        .next()
-        .unwrap()
-        .split(',')
-        .map(|pair| {
-            let Some((from, to)) = pair.split_once('-') else {
-                return Err(anyhow!("Bad pair: {pair}"));
-            };
-            let from = from.parse()?;
-            let to = to.parse()?;
-            Ok((from..to))
-        })
-        .collect::<_>()
+    Ok(parse::ranges(input)?
+        .into_iter()
+        .map(|(from, to)| from..to)
+        .collect())
 }
 
 fn count_digits(n: u64) -> u64 {
@@ -101,7 +100,7 @@ impl Iterator for GroupsOf {
             return None;
         }
 
-        let tens = 10u32.pow(self.digits) as u64;
+        let tens = 10u64.pow(self.digits);
         let r = self.n % tens;
         self.n /= tens;
 
@@ -109,31 +108,67 @@ impl Iterator for GroupsOf {
     }
 }
 
+/// Reference for part one: compares the two halves of the ID as text.
+fn naive_01(input: &[Range<u64>]) -> u64 {
+    resolver(input, |n| {
+        let s = n.to_string();
+        let (a, b) = s.split_at(s.len() / 2);
+        a == b
+    })
+}
+
+/// Reference for part two: tries every prefix of the ID as the repeated group.
+fn naive_02(input: &[Range<u64>]) -> u64 {
+    resolver(input, |n| {
+        let s = n.to_string();
+        (1..s.len()).any(|k| s.len() % k == 0 && s[..k].repeat(s.len() / k) == s)
+    })
+}
+
+/// `size` ranges of up to a thousand IDs, each with its own number of digits.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let ranges = (0..size)
+        .map(|_| {
+            let digits = rng.range(1..=10) as u32;
+            let from = rng.range(1..=10u64.pow(digits));
+            format!("{from}-{}", from + rng.below(1000))
+        })
+        .collect::<Vec<_>>();
+    ranges.join(",") + "\n"
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_invalid_id_part1() {
-        assert_eq!(true, invalid_id_part1(&55));
-        assert_eq!(false, invalid_id_part1(&5));
-        assert_eq!(false, invalid_id_part1(&54));
+        assert!(invalid_id_part1(&55));
+        assert!(!invalid_id_part1(&5));
+        assert!(!invalid_id_part1(&54));
     }
 
     #[test]
     fn test_groups_of() {
         assert_eq!(
             vec![123, 123, 123],
-            GroupsOf::new(123123123, 3).into_iter().collect::<Vec<_>>()
+            GroupsOf::new(123123123, 3).collect::<Vec<_>>()
         )
     }
 
     #[test]
     fn test_invalid_id_part2() {
-        assert_eq!(true, invalid_id_part2(&55));
-        assert_eq!(false, invalid_id_part2(&5));
-        assert_eq!(false, invalid_id_part2(&54));
-        assert_eq!(true, invalid_id_part2(&545454));
-        assert_eq!(true, invalid_id_part2(&824824824));
+        assert!(invalid_id_part2(&55));
+        assert!(!invalid_id_part2(&5));
+        assert!(!invalid_id_part2(&54));
+        assert!(invalid_id_part2(&545454));
+        assert!(invalid_id_part2(&824824824));
     }
+
+    runner::standard_tests!(
+        day,
+        size: 20,
+        reference: (50, 20),
+        near_valid: "([0-9]{0,6}[,\\-\\n]){0,10}",
+    );
 }
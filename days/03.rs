@@ -1,88 +1,197 @@
 use anyhow::{Result, anyhow};
+use runner::{Day, Rng};
 
 type Input = Vec<Vec<u8>>;
 
+/// Batteries turned on in each bank for part two, so also the fewest a bank can hold.
+const BATTERIES: usize = 12;
+
+pub fn day() -> Day {
+    Day::new(
+        3,
+        include_str!("../inputs/03/input.txt"),
+        parse_input,
+        part_01,
+        part_02,
+    )
+    .with_generator(generate)
+    .with_reference(
+        parse_input,
+        |input| naive(input, 2),
+        |input| naive(input, BATTERIES),
+    )
+}
+
 fn main() -> Result<()> {
-    let input = parse_input(include_str!("../inputs/03/input.txt"))?;
+    day().main()
+}
 
-    let part1 = part_01(&input);
-    println!("Part 01: {part1}");
-    let part2 = part_02(&input);
-    println!("Part 02: {part2}");
-    Ok(())
+fn part_01(input: &Input) -> Result<u64> {
+    joltage(input, 2)
 }
 
-fn part_01(input: &Input) -> u64 {
-    input
-        .iter()
-        .map(|bank| {
-            let it = bank.as_slice()[0..(bank.len() - 1)].iter().copied();
-            let (first_index, first_digit) =
-                highest(it).expect("There should be at least one item in a bank");
-            let it = bank.as_slice()[(first_index + 1)..].iter().copied();
-            let (_second_index, second_digit) =
-                highest(it).expect("There should be a second item in the bank");
-            first_digit as u64 * 10 + second_digit as u64
+fn part_02(input: &Input) -> Result<u64> {
+    joltage(input, BATTERIES)
+}
+
+fn joltage(input: &Input, batteries: usize) -> Result<u64> {
+    input.iter().try_fold(0u64, |total, bank| {
+        max_subsequence(bank, batteries)
+            .ok_or_else(|| anyhow!("A bank has fewer than {batteries} batteries"))?
+            .value()
+            .and_then(|v| total.checked_add(v))
+            .ok_or_else(|| anyhow!("Joltage of {batteries} batteries overflows"))
+    })
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Subsequence {
+    digits: Vec<u8>,
+    indices: Vec<usize>,
+}
+
+impl Subsequence {
+    /// The digits read as a number, if it fits in a `u64`: at most 19 digits always do.
+    fn value(&self) -> Option<u64> {
+        self.digits.iter().try_fold(0u64, |acc, digit| {
+            acc.checked_mul(10)?.checked_add(*digit as u64)
         })
-        .sum()
+    }
 }
 
-fn part_02(input: &Input) -> u64 {
-    const DIGITS: usize = 12;
+/// Picks the `k` digits, in their original order, forming the largest number.
+///
+/// Walks the digits once while keeping a monotonic stack of chosen indices: a digit evicts the
+/// digits before it as long as it beats them and enough digits remain to still fill `k` slots.
+fn max_subsequence(digits: &[u8], k: usize) -> Option<Subsequence> {
+    if k > digits.len() {
+        return None;
+    }
+
+    let mut droppable = digits.len() - k;
+    let mut stack: Vec<usize> = Vec::with_capacity(digits.len());
+    for (index, digit) in digits.iter().enumerate() {
+        while droppable > 0
+            && let Some(&top) = stack.last()
+            && *digit > digits[top]
+        {
+            stack.pop();
+            droppable -= 1;
+        }
+        stack.push(index);
+    }
+    stack.truncate(k);
+
+    Some(Subsequence {
+        digits: stack.iter().map(|i| digits[*i]).collect(),
+        indices: stack,
+    })
+}
+
+/// Reference for both parts: the best `k` digits of every suffix of a bank, by dynamic
+/// programming rather than the greedy stack.
+fn naive(input: &Input, batteries: usize) -> u64 {
     input
         .iter()
         .map(|bank| {
-            let mut start = 0;
-            let mut res = 0;
-            for i in 0..DIGITS {
-                let digits_left = DIGITS - i;
-                let it = bank.as_slice()[start..(bank.len() - (digits_left - 1))]
-                    .iter()
-                    .copied();
-                let (index, digit) =
-                    highest(it).expect("There should be at least one item in a bank");
-                res *= 10;
-                res += digit as u64;
-                start += index + 1;
+            // best[j] holds the largest number made of j digits from the suffix seen so far.
+            let mut best: Vec<Option<u64>> = vec![None; batteries + 1];
+            best[0] = Some(0);
+            for (i, digit) in bank.iter().enumerate().rev() {
+                let len = bank.len() - i;
+                for j in (1..=batteries.min(len)).rev() {
+                    let take =
+                        best[j - 1].map(|rest| *digit as u64 * 10u64.pow(j as u32 - 1) + rest);
+                    best[j] = best[j].max(take);
+                }
             }
-            res
+            best[batteries].expect("There should be enough batteries in a bank")
         })
         .sum()
 }
 
-fn highest<I>(iter: I) -> Option<(usize, u8)>
-where
-    I: DoubleEndedIterator<Item = u8> + ExactSizeIterator,
-{
-    // We need to reverse the list because max_by takes the last maximum while we want the first
-    iter.enumerate().rev().max_by(|x, y| x.1.cmp(&y.1))
+fn parse_input(input: &str) -> Result<Input> {
+    Ok(parse::lines(input, |line| {
+        let bank = line
+            .chars()
+            .map(|ch| ch.to_digit(10).map(|d| d as u8))
+            .collect::<Option<Vec<u8>>>()
+            .ok_or_else(|| "not a bank of digits".to_string())?;
+        if bank.len() < BATTERIES {
+            return Err(format!("fewer than {BATTERIES} batteries in a bank"));
+        }
+        Ok(bank)
+    })?)
 }
 
-fn parse_input(input: &str) -> Result<Input> {
-    input
-        .lines()
-        .map(|line| {
-            line.chars()
-                .map(|ch| ch.to_digit(10).map(|d| d as u8))
-                .collect::<Option<Vec<u8>>>()
-        })
-        .collect::<Option<Input>>()
-        .ok_or_else(|| anyhow!("Failed to parse"))
+/// `size` banks of a hundred batteries.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut out = String::new();
+    for _ in 0..size {
+        out.extend((0..100).map(|_| char::from(b'1' + rng.below(9) as u8)));
+        out.push('\n');
+    }
+    out
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{parse_input, part_01, part_02};
+    use crate::{Subsequence, day, joltage, max_subsequence, parse_input, part_01, part_02};
 
     #[test]
     fn max_joltage_01() {
         let input = parse_input("811111111111119").unwrap();
-        assert_eq!(89, part_01(&input));
+        assert_eq!(89, part_01(&input).unwrap());
     }
 
     #[test]
     fn max_joltage_02() {
         let input = parse_input("987654321111111").unwrap();
-        assert_eq!(987654321111, part_02(&input));
+        assert_eq!(987654321111, part_02(&input).unwrap());
+    }
+
+    #[test]
+    fn test_short_bank() {
+        assert_eq!(
+            "line 1: fewer than 12 batteries in a bank in \"12345\"",
+            parse_input("12345\n").unwrap_err().to_string()
+        );
+        assert!(parse_input("123456789012\n").is_ok());
     }
+
+    #[test]
+    fn test_max_subsequence() {
+        assert_eq!(
+            Some(Subsequence {
+                digits: vec![9, 4, 3],
+                indices: vec![1, 3, 4],
+            }),
+            max_subsequence(&[1, 9, 2, 4, 3, 1], 3)
+        );
+        assert_eq!(None, max_subsequence(&[1, 2], 3));
+    }
+
+    #[test]
+    fn test_value() {
+        assert_eq!(
+            Some(943),
+            max_subsequence(&[1, 9, 2, 4, 3, 1], 3).unwrap().value()
+        );
+
+        let long = max_subsequence(&[9; 25], 20).unwrap();
+        assert_eq!(None, long.value());
+        assert!(joltage(&vec![vec![9; 25]], 20).is_err());
+        assert!(joltage(&vec![vec![1, 2]], 3).is_err());
+        assert_eq!(
+            Some(9_999_999_999_999_999_999),
+            max_subsequence(&[9; 25], 19).unwrap().value()
+        );
+    }
+
+    runner::standard_tests!(
+        day,
+        size: 20,
+        reference: (50, 20),
+        near_valid: "[0-9a\\n]{0,60}",
+    );
 }
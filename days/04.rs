@@ -1,5 +1,8 @@
-use anyhow::{Result, anyhow};
-use grid::{Cell, Grid};
+use std::{fs::File, io::BufWriter};
+
+use anyhow::Result;
+use grid::{Cell, Grid, Rgb};
+use runner::{Day, Rng};
 
 type Input = Grid<Stuff>;
 
@@ -9,13 +12,34 @@ enum Stuff {
     Roll,
 }
 
+pub fn day() -> Day {
+    Day::new(
+        4,
+        include_str!("../inputs/04/input.txt"),
+        parse_input,
+        part_01,
+        part_02,
+    )
+    .with_generator(generate)
+    .with_reference(parse_input, naive_01, naive_02)
+}
+
 fn main() -> Result<()> {
-    let input = parse_input(include_str!("../inputs/04/input.txt"))?;
+    let day = day();
+    day.main()?;
 
-    let part1 = part_01(&input);
-    println!("Part 01: {part1}");
-    let part2 = part_02(input.clone());
-    println!("Part 02: {part2}");
+    // `--png <file>` saves the rolls as an image
+    let args = std::env::args().collect::<Vec<_>>();
+    if let [_, flag, path] = args.as_slice()
+        && flag == "--png"
+    {
+        let input = parse_input(day.input)?;
+        let image = input.to_image(4, |cell| match cell {
+            Stuff::Empty => Rgb::WHITE,
+            Stuff::Roll => Rgb(139, 69, 19),
+        });
+        image.write_png(BufWriter::new(File::create(path)?))?;
+    }
     Ok(())
 }
 
@@ -42,45 +66,170 @@ fn part_01(input: &Input) -> usize {
     count
 }
 
-fn part_02(mut input: Input) -> usize {
-    let mut count = 0;
-    loop {
-        let to_remove = input
-            .iter_cells()
-            .filter_map(|cell| {
-                if cell.val == &Stuff::Roll && removable(&input, &cell) {
-                    Some((cell.row, cell.col))
-                } else {
-                    None
+fn part_02(input: &Input) -> usize {
+    remove_rolls(input).total()
+}
+
+/// How many rolls each successive wave of removals took out.
+#[derive(Debug, PartialEq, Eq)]
+struct Removal {
+    waves: Vec<usize>,
+}
+
+impl Removal {
+    fn total(&self) -> usize {
+        self.waves.iter().sum()
+    }
+}
+
+fn roll_neighbours(grid: &Grid<Stuff>, row: usize, col: usize) -> u8 {
+    grid.neighbours(row, col)
+        .iter()
+        .filter(|(rr, cc)| matches!(grid.get(*rr, *cc).unwrap().val, &Stuff::Roll))
+        .count() as u8
+}
+
+fn remove_rolls(input: &Input) -> Removal {
+    let mut grid = input.clone();
+    let mut counts = Grid::with_capacity(grid.row_count());
+    for row in 0..grid.row_count() {
+        counts.add_row(
+            (0..grid.col_count())
+                .map(|col| roll_neighbours(&grid, row, col))
+                .collect(),
+        );
+    }
+
+    let mut wave = grid
+        .iter_cells()
+        .filter(|cell| cell.val == &Stuff::Roll && *counts.get(cell.row, cell.col).unwrap().val < 4)
+        .map(|cell| (cell.row, cell.col))
+        .collect::<Vec<_>>();
+
+    let mut waves = vec![];
+    while !wave.is_empty() {
+        waves.push(wave.len());
+
+        // Take the whole wave out first so its rolls don't decrement each other
+        for (row, col) in wave.iter() {
+            grid.set(*row, *col, Stuff::Empty);
+        }
+
+        let mut next = vec![];
+        for (row, col) in wave.drain(..) {
+            for (rr, cc) in grid.neighbours(row, col) {
+                if grid.get(rr, cc).unwrap().val != &Stuff::Roll {
+                    continue;
                 }
-            })
-            .collect::<Vec<_>>();
 
-        if to_remove.is_empty() {
-            break;
+                let count = *counts.get(rr, cc).unwrap().val - 1;
+                counts.set(rr, cc, count);
+                // A roll only becomes removable once, when it drops from 4 to 3 neighbours
+                if count == 3 {
+                    next.push((rr, cc));
+                }
+            }
         }
-        count += to_remove.len();
+        wave = next;
+    }
+
+    Removal { waves }
+}
+
+/// Reference for part one: looks at the eight offsets around each roll itself, rather than asking
+/// the grid for its neighbours.
+fn naive_01(input: &Input) -> usize {
+    let is_roll = |row: usize, col: usize, (dr, dc): (isize, isize)| {
+        let (Some(row), Some(col)) = (row.checked_add_signed(dr), col.checked_add_signed(dc))
+        else {
+            return false;
+        };
+        input
+            .get(row, col)
+            .is_some_and(|cell| cell.val == &Stuff::Roll)
+    };
+
+    input
+        .iter_cells()
+        .filter(|cell| cell.val == &Stuff::Roll)
+        .filter(|cell| {
+            let around = (-1..=1)
+                .flat_map(|dr| (-1..=1).map(move |dc| (dr, dc)))
+                .filter(|&offset| offset != (0, 0) && is_roll(cell.row, cell.col, offset))
+                .count();
+            around < 4
+        })
+        .count()
+}
 
-        for (row, col) in to_remove {
-            input.set(row, col, Stuff::Empty);
+/// Reference for part two: rescans the whole floor after every wave instead of tracking counts.
+fn naive_02(input: &Input) -> usize {
+    let mut grid = input.clone();
+    let mut total = 0;
+    loop {
+        let wave = grid
+            .iter_cells()
+            .filter(|cell| cell.val == &Stuff::Roll && removable(&grid, cell))
+            .map(|cell| (cell.row, cell.col))
+            .collect::<Vec<_>>();
+        if wave.is_empty() {
+            return total;
+        }
+        total += wave.len();
+        for (row, col) in wave {
+            grid.set(row, col, Stuff::Empty);
         }
     }
-    count
 }
 
 fn parse_input(input: &str) -> Result<Input> {
-    let mut grid = Grid::new();
-    for line in input.lines() {
-        grid.add_row(
-            line.chars()
-                .map(|ch| match ch {
-                    '.' => Ok(Stuff::Empty),
-                    '@' => Ok(Stuff::Roll),
-                    _ => Err(anyhow!("Unknown cell {ch}")),
-                })
-                .collect::<Result<Vec<Stuff>>>()?,
-        );
-    }
+    let rows = parse::chars(input, |ch| match ch {
+        '.' => Ok(Stuff::Empty),
+        '@' => Ok(Stuff::Roll),
+        _ => Err(format!("unknown cell {ch:?}")),
+    })?;
 
+    let mut grid = Grid::with_capacity(rows.len());
+    for row in rows {
+        grid.add_row(row);
+    }
     Ok(grid)
 }
+
+/// A `size` by `size` floor, with rolls on about 60% of it.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut out = String::new();
+    for _ in 0..size {
+        out.extend((0..size).map(|_| if rng.chance(0.6) { '@' } else { '.' }));
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{day, parse_input, part_01, part_02, remove_rolls};
+
+    const SAMPLE: &str = include_str!("../inputs/04/sample.txt");
+
+    #[test]
+    fn test_sample() {
+        let input = parse_input(SAMPLE).unwrap();
+        assert_eq!(13, part_01(&input));
+        assert_eq!(43, part_02(&input));
+    }
+
+    #[test]
+    fn test_waves() {
+        let input = parse_input(SAMPLE).unwrap();
+        let removal = remove_rolls(&input);
+        assert_eq!(vec![13, 12, 7, 5, 2, 1, 1, 1, 1], removal.waves);
+    }
+
+    runner::standard_tests!(
+        day,
+        size: 30,
+        reference: (50, 12),
+        near_valid: "[.@x\\n]{0,60}",
+    );
+}
@@ -1,20 +1,27 @@
-use std::ops::RangeInclusive;
+use std::{collections::HashSet, ops::RangeInclusive};
 
 use anyhow::{Result, anyhow};
+use runner::{Day, Rng};
 
 struct Input {
     ranges: Vec<RangeInclusive<u64>>,
     ingredients: Vec<u64>,
 }
 
-fn main() -> Result<()> {
-    let input = parse_input(include_str!("../inputs/05/input.txt"))?;
+pub fn day() -> Day {
+    Day::new(
+        5,
+        include_str!("../inputs/05/input.txt"),
+        parse_input,
+        part_01,
+        part_02,
+    )
+    .with_generator(generate)
+    .with_reference(parse_input, naive_01, naive_02)
+}
 
-    let part1 = part_01(&input);
-    println!("Part 01: {part1}");
-    let part2 = part_02(&input);
-    println!("Part 02: {part2}");
-    Ok(())
+fn main() -> Result<()> {
+    day().main()
 }
 
 fn part_01(input: &Input) -> usize {
@@ -25,24 +32,28 @@ fn part_01(input: &Input) -> usize {
         .count()
 }
 
-fn part_02(input: &Input) -> u64 {
+fn part_02(input: &Input) -> Result<u64> {
     let mut ranges = input.ranges.clone();
-    ranges.sort_by_key(|r| r.start().clone());
-
-    let mut total = 0;
+    ranges.sort_by_key(|r| *r.start());
+
+    let mut total = 0u64;
+    let mut add = |from: u64, to: u64| {
+        total = (to - from)
+            .checked_add(1)
+            .and_then(|count| total.checked_add(count))
+            .ok_or_else(|| anyhow!("Too many fresh IDs to count"))?;
+        anyhow::Ok(())
+    };
     let mut it = ranges.iter().peekable();
-    'outer: loop {
-        let Some(cur) = it.next() else {
-            break;
-        };
+    'outer: while let Some(cur) = it.next() {
         loop {
             let Some(next) = it.peek() else {
-                total += *cur.end() - *cur.start() + 1;
+                add(*cur.start(), *cur.end())?;
                 break;
             };
 
             if next.start() > cur.end() {
-                total += *cur.end() - *cur.start() + 1;
+                add(*cur.start(), *cur.end())?;
                 continue 'outer;
             }
 
@@ -53,39 +64,104 @@ fn part_02(input: &Input) -> u64 {
             }
 
             // Overlaps but extends, only add the part
-            total += next.start() - cur.start();
+            if next.start() > cur.start() {
+                add(*cur.start(), next.start() - 1)?;
+            }
             continue 'outer;
         }
     }
 
-    total
+    Ok(total)
+}
+
+/// Reference for part one: lists every fresh ID, then looks the ingredients up in it.
+fn naive_01(input: &Input) -> usize {
+    let fresh = input
+        .ranges
+        .iter()
+        .flat_map(|r| r.clone())
+        .collect::<HashSet<_>>();
+    input
+        .ingredients
+        .iter()
+        .filter(|ing| fresh.contains(ing))
+        .count()
+}
+
+/// Reference for part two: checks every ID between the lowest and highest fresh ones.
+fn naive_02(input: &Input) -> u64 {
+    let from = input.ranges.iter().map(|r| *r.start()).min().unwrap_or(1);
+    let to = input.ranges.iter().map(|r| *r.end()).max().unwrap_or(0);
+    (from..=to)
+        .filter(|id| input.ranges.iter().any(|r| r.contains(id)))
+        .count() as u64
 }
 
 fn parse_input(input: &str) -> Result<Input> {
-    let Some((rgs, ings)) = input.split_once("\n\n") else {
-        return Err(anyhow!("No parts split"));
+    let [ranges, ingredients] = parse::sections(input)[..] else {
+        return Err(anyhow!(
+            "Expected ranges and ingredients separated by a blank line"
+        ));
     };
 
-    let ranges = rgs
-        .lines()
-        .map(|line| {
-            let Some((from, to)) = line.split_once('-') else {
-                return Err(anyhow!("No - separator"));
-            };
-            let from = from.parse::<u64>()?;
-            let to = to.parse::<u64>()?;
-
-            Ok(from..=to)
+    let ranges = ranges
+        .parse(parse::ranges)?
+        .into_iter()
+        .map(|(from, to)| {
+            if from > to {
+                Err(anyhow!("Range {from}-{to} ends before it starts"))
+            } else {
+                Ok(from..=to)
+            }
         })
-        .collect::<Result<Vec<RangeInclusive<u64>>>>()?;
-
-    let ingredients = ings
-        .lines()
-        .map(|l| Ok(l.parse::<u64>()?))
-        .collect::<Result<Vec<u64>>>()?;
+        .collect::<Result<_>>()?;
+    let ingredients = ingredients.parse(parse::numbers)?;
 
     Ok(Input {
         ranges,
         ingredients,
     })
 }
+
+/// `size` fresh ranges of up to 200 IDs and `size` ingredients, with IDs up to a hundred times
+/// `size` so that about half of them are fresh.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+    let max = 100 * size as u64;
+    let mut out = String::new();
+    for _ in 0..size {
+        let from = rng.range(1..=max);
+        out += &format!("{from}-{}\n", from + rng.below(200));
+    }
+    out.push('\n');
+    for _ in 0..size {
+        out += &format!("{}\n", rng.range(1..=max));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{day, parse_input, part_02};
+
+    #[test]
+    fn test_reversed_range() {
+        assert!(parse_input("5-1\n\n3\n").is_err());
+        assert!(parse_input("5-5\n\n3\n").is_ok());
+    }
+
+    #[test]
+    fn test_too_many_fresh() {
+        let input = parse_input("0-18446744073709551615\n\n3\n").unwrap();
+        assert!(part_02(&input).is_err());
+        let input = parse_input("1-18446744073709551615\n\n3\n").unwrap();
+        assert_eq!(u64::MAX, part_02(&input).unwrap());
+    }
+
+    runner::standard_tests!(
+        day,
+        size: 20,
+        reference: (100, 20),
+        near_valid: "[0-9\\-\\n]{0,60}",
+    );
+}
@@ -1,122 +1,420 @@
-use anyhow::Result;
+use std::{collections::HashMap, ops::Range, str::FromStr};
+
+use anyhow::{Result, anyhow};
+use grid::Grid;
+use parse::ParseError;
+use runner::{Day, Rng};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Op {
     Add,
+    Sub,
     Mul,
+    Div,
+    Rem,
+    Min,
+    Max,
+    Concat,
 }
 
-struct Equation {
-    nums: Vec<u64>,
+impl Op {
+    fn apply(&self, a: u64, b: u64) -> Result<u64> {
+        let res = match self {
+            Op::Add => a.checked_add(b),
+            Op::Sub => a.checked_sub(b),
+            Op::Mul => a.checked_mul(b),
+            Op::Div => a.checked_div(b),
+            Op::Rem => a.checked_rem(b),
+            Op::Min => Some(a.min(b)),
+            Op::Max => Some(a.max(b)),
+            Op::Concat => 10u64
+                .checked_pow(b.checked_ilog10().unwrap_or(0) + 1)
+                .and_then(|tens| a.checked_mul(tens))
+                .and_then(|a| a.checked_add(b)),
+        };
+
+        res.ok_or_else(|| anyhow!("{a} {self:?} {b} is out of range"))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Operator {
     op: Op,
+    precedence: u8,
 }
 
-impl Equation {
-    fn compute(&self) -> u64 {
-        match self.op {
-            Op::Add => self.nums.iter().sum(),
-            Op::Mul => self.nums.iter().product(),
+/// Maps worksheet symbols to operators. Higher precedence binds tighter, ties associate left.
+struct Operators {
+    table: HashMap<char, Operator>,
+}
+
+impl Operators {
+    fn with(mut self, symbol: char, op: Op, precedence: u8) -> Self {
+        self.table.insert(symbol, Operator { op, precedence });
+        self
+    }
+
+    fn get(&self, symbol: char) -> Result<Operator> {
+        self.table
+            .get(&symbol)
+            .copied()
+            .ok_or_else(|| anyhow!("Unknown operator {symbol}"))
+    }
+}
+
+impl Default for Operators {
+    fn default() -> Self {
+        Self {
+            table: HashMap::new(),
         }
+        .with('<', Op::Min, 0)
+        .with('>', Op::Max, 0)
+        .with('+', Op::Add, 1)
+        .with('-', Op::Sub, 1)
+        .with('*', Op::Mul, 2)
+        .with('/', Op::Div, 2)
+        .with('%', Op::Rem, 2)
+        .with('|', Op::Concat, 3)
     }
 }
 
-type Input = Vec<Equation>;
+struct Equation {
+    nums: Vec<u64>,
+    ops: Vec<Operator>,
+}
 
-fn main() -> Result<()> {
-    let raw_input = include_str!("../inputs/06/input.txt");
-    let input = parse_input(raw_input)?;
+impl Equation {
+    /// A single operator goes between every pair of numbers, otherwise there must be one per gap.
+    fn compute(&self) -> Result<u64> {
+        let Some((first, rest)) = self.nums.split_first() else {
+            return Err(anyhow!("Equation without numbers"));
+        };
 
-    let part1 = part_01(&input);
-    println!("Part 01: {part1}");
-    let part2 = part_02(raw_input);
-    println!("Part 02: {part2}");
+        let ops = match self.ops.as_slice() {
+            [op] => vec![*op; rest.len()],
+            ops if ops.len() == rest.len() => ops.to_vec(),
+            ops => {
+                return Err(anyhow!(
+                    "{} operators for {} numbers",
+                    ops.len(),
+                    self.nums.len()
+                ));
+            }
+        };
+
+        let mut values = vec![*first];
+        let mut pending: Vec<Operator> = vec![];
+        for (op, num) in ops.into_iter().zip(rest) {
+            while let Some(top) = pending.last()
+                && top.precedence >= op.precedence
+            {
+                reduce(&mut values, pending.pop().unwrap().op)?;
+            }
+            pending.push(op);
+            values.push(*num);
+        }
+
+        while let Some(top) = pending.pop() {
+            reduce(&mut values, top.op)?;
+        }
+
+        Ok(values[0])
+    }
+}
+
+fn reduce(values: &mut Vec<u64>, op: Op) -> Result<()> {
+    let b = values.pop().unwrap();
+    let a = values.pop().unwrap();
+    values.push(op.apply(a, b)?);
     Ok(())
 }
 
-fn part_01(input: &Input) -> u64 {
-    input.iter().map(|eq| eq.compute()).sum()
+/// A single problem of the worksheet: the block of digit rows between two blank columns.
+struct Problem {
+    digits: Grid<char>,
+    ops: Vec<Operator>,
 }
 
-fn part_02(input: &str) -> u64 {
-    let mat: Vec<Vec<char>> = input.lines().map(|l| l.chars().collect()).collect();
-    let mut total = 0;
+impl Problem {
+    /// Reads every row of the block as a number, top to bottom.
+    fn by_rows(&self) -> Result<Equation> {
+        Self::read_rows(&self.digits, self.ops.clone())
+    }
 
-    let rows = mat.len();
-    let cols = mat.iter().map(|r| r.len()).max().unwrap();
+    /// Reads every column of the block as a number, right to left.
+    fn by_columns(&self) -> Result<Equation> {
+        // A quarter turn counter-clockwise puts the rightmost column, top to bottom, first
+        let columns = self
+            .digits
+            .rotate_ccw()
+            .ok_or_else(|| anyhow!("The digits of a problem should line up"))?;
+        Self::read_rows(&columns, self.ops.clone())
+    }
 
-    let mut buf: Vec<u64> = vec![];
-    let mut next_op = Op::Add;
+    fn read_rows(digits: &Grid<char>, ops: Vec<Operator>) -> Result<Equation> {
+        let nums = (0..digits.row_count())
+            .map(|row| read_number(digits.row(row).map(|c| c.val)))
+            .filter_map(Result::transpose)
+            .collect::<Result<_>>()?;
+        Ok(Equation { nums, ops })
+    }
+}
 
-    for j in 0..cols {
-        let mut next_num = 0;
-        let data = mat.iter().flat_map(|row| row.get(j)).collect::<Vec<_>>();
+/// The digits among `chars` as a number, or `None` if there are none.
+fn read_number<'a, I>(chars: I) -> Result<Option<u64>>
+where
+    I: Iterator<Item = &'a char>,
+{
+    chars
+        .filter_map(|ch| ch.to_digit(10))
+        .try_fold(None, |acc: Option<u64>, d| {
+            acc.unwrap_or(0)
+                .checked_mul(10)
+                .and_then(|n| n.checked_add(d as u64))
+                .map(Some)
+                .ok_or_else(|| anyhow!("Number too large for a u64"))
+        })
+}
 
-        if data.iter().all(|ch| ch.is_whitespace()) {
-            if next_op == Op::Add {
-                total += buf.iter().sum::<u64>();
-            } else {
-                total += buf.iter().product::<u64>();
-            }
+struct Worksheet {
+    problems: Vec<Problem>,
+}
 
-            buf.clear();
+impl Worksheet {
+    fn parse_with(s: &str, operators: &Operators) -> Result<Self> {
+        let mut numbered = s
+            .lines()
+            .enumerate()
+            .filter(|(_, l)| !l.trim().is_empty())
+            .map(|(i, l)| (i + 1, l))
+            .collect::<Vec<_>>();
+        let Some((ops_line, ops_text)) = numbered.pop() else {
+            return Err(anyhow!("Empty worksheet"));
+        };
+        let at_ops = |reason: String| ParseError::new(ops_line, ops_text, reason);
 
-            continue;
+        if let Some((line, text)) = numbered.iter().find(|(_, l)| {
+            l.chars()
+                .any(|ch| !ch.is_ascii_digit() && !ch.is_whitespace())
+        }) {
+            return Err(ParseError::new(*line, text, "expected only digits and spaces").into());
         }
 
-        for ch in data {
-            match *ch {
-                '+' => next_op = Op::Add,
-                '*' => next_op = Op::Mul,
-                '0'..='9' => {
-                    next_num *= 10;
-                    next_num += ch.to_digit(10).unwrap() as u64;
+        let lines = numbered
+            .iter()
+            .map(|(_, l)| l.chars().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let ops = ops_text.chars().collect::<Vec<_>>();
+
+        let width = lines.iter().chain([&ops]).map(|l| l.len()).max().unwrap();
+        let pad = |line: &[char], cols: Range<usize>| {
+            cols.map(|c| line.get(c).copied().unwrap_or(' '))
+                .collect::<Vec<_>>()
+        };
+
+        let blank = |col: usize| {
+            lines
+                .iter()
+                .chain([&ops])
+                .all(|l| l.get(col).is_none_or(|ch| ch.is_whitespace()))
+        };
+
+        let mut problems = vec![];
+        let mut start = 0;
+        for col in 0..=width {
+            if col < width && !blank(col) {
+                continue;
+            }
+
+            if start < col {
+                let ops = pad(&ops, start..col)
+                    .into_iter()
+                    .filter(|ch| !ch.is_whitespace())
+                    .map(|ch| operators.get(ch).map_err(|e| at_ops(e.to_string())))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if ops.is_empty() {
+                    return Err(at_ops(format!("no operator for columns {start}..{col}")).into());
+                }
+                let mut digits = Grid::with_capacity(lines.len());
+                for line in lines.iter() {
+                    digits.add_row(pad(line, start..col));
                 }
-                _ => {}
+                problems.push(Problem { digits, ops });
             }
+            start = col + 1;
         }
 
-        buf.push(next_num);
+        Ok(Worksheet { problems })
     }
+}
 
-    if next_op == Op::Add {
-        total += buf.iter().sum::<u64>();
-    } else {
-        total += buf.iter().product::<u64>();
+impl FromStr for Worksheet {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse_with(s, &Operators::default())
     }
+}
+
+type Input = Worksheet;
+
+pub fn day() -> Day {
+    Day::new(
+        6,
+        include_str!("../inputs/06/input.txt"),
+        parse_input,
+        part_01,
+        part_02,
+    )
+    .with_generator(generate)
+}
+
+fn main() -> Result<()> {
+    day().main()
+}
 
-    total
+fn part_01(input: &Input) -> Result<u64> {
+    total(input.problems.iter().map(|p| p.by_rows()?.compute()))
+}
+
+fn part_02(input: &Input) -> Result<u64> {
+    total(input.problems.iter().map(|p| p.by_columns()?.compute()))
+}
+
+fn total(mut answers: impl Iterator<Item = Result<u64>>) -> Result<u64> {
+    answers.try_fold(0u64, |total, answer| {
+        total
+            .checked_add(answer?)
+            .ok_or_else(|| anyhow!("Grand total is out of range"))
+    })
 }
 
 fn parse_input(input: &str) -> Result<Input> {
-    let mut nums: Vec<Vec<u64>> = vec![];
-    let mut ops: Vec<Op> = vec![];
-    for line in input.lines() {
-        if line.starts_with('*') || line.starts_with('+') {
-            ops = line
-                .split_whitespace()
-                .map(|item| match item {
-                    "+" => Op::Add,
-                    "*" => Op::Mul,
-                    _ => unreachable!(),
-                })
-                .collect();
-            break;
+    input.parse()
+}
+
+/// `size` problems (at least one) of four numbers, each up to three digits and aligned to either
+/// side.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    const ROWS: usize = 4;
+    let size = size.max(1);
+    let mut lines = vec![String::new(); ROWS + 1];
+    for i in 0..size {
+        let nums = (0..ROWS)
+            .map(|_| {
+                let digits = rng.range(1..=3) as u32;
+                rng.range(10u64.pow(digits - 1)..=10u64.pow(digits) - 1)
+                    .to_string()
+            })
+            .collect::<Vec<_>>();
+        let width = nums.iter().map(|n| n.len()).max().unwrap();
+        let left = rng.chance(0.5);
+
+        for (line, num) in lines.iter_mut().zip(nums) {
+            if i > 0 {
+                line.push(' ');
+            }
+            *line += &if left {
+                format!("{num:<width$}")
+            } else {
+                format!("{num:>width$}")
+            };
         }
+        let op = rng.pick(&['+', '*']);
+        *lines.last_mut().unwrap() += &format!("{}{op:<width$}", if i > 0 { " " } else { "" });
+    }
 
-        nums.push(
-            line.split_whitespace()
-                .map(|n| Ok(n.parse::<u64>()?))
-                .collect::<Result<_>>()?,
-        );
+    lines.iter().map(|l| format!("{l}\n")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Op, Operators, Worksheet, day, parse_input, part_01, part_02};
+
+    const SAMPLE: &str = include_str!("../inputs/06/sample.txt");
+
+    #[test]
+    fn test_worksheet() {
+        let input = parse_input(SAMPLE).unwrap();
+        assert_eq!(4, input.problems.len());
+
+        let first = &input.problems[0];
+        assert_eq!(Op::Mul, first.ops[0].op);
+        assert_eq!(vec![123, 45, 6], first.by_rows().unwrap().nums);
+        assert_eq!(vec![356, 24, 1], first.by_columns().unwrap().nums);
+    }
+
+    #[test]
+    fn test_sample() {
+        let input = parse_input(SAMPLE).unwrap();
+        assert_eq!(4277556, part_01(&input).unwrap());
+        assert_eq!(3263827, part_02(&input).unwrap());
+    }
+
+    #[test]
+    fn test_precedence() {
+        // A single operator still applies between every number
+        let input = parse_input("2\n3\n4\n-").unwrap();
+        assert!(input.problems[0].by_rows().unwrap().compute().is_err());
+        let input = parse_input("9\n3\n4\n-").unwrap();
+        assert_eq!(2, input.problems[0].by_rows().unwrap().compute().unwrap());
+
+        let input = parse_input("2\n3\n4\n5\n+*|").unwrap();
+        let eq = input.problems[0].by_rows().unwrap();
+        assert_eq!(2 + 3 * 45, eq.compute().unwrap());
+
+        let operators = Operators::default().with('+', Op::Add, 5);
+        let input = Worksheet::parse_with("2\n3\n4\n+*", &operators).unwrap();
+        assert_eq!(20, input.problems[0].by_rows().unwrap().compute().unwrap());
     }
 
-    let mut equations = vec![];
-    for i in 0..nums[0].len() {
-        equations.push(Equation {
-            nums: nums.iter().map(|row| row[i]).collect(),
-            op: ops[i],
-        });
+    #[test]
+    fn test_checked() {
+        let input = parse_input("1\n2\n-").unwrap();
+        assert!(part_01(&input).is_err());
+
+        let input = parse_input("1\n0\n/").unwrap();
+        assert!(part_01(&input).is_err());
+
+        let input = parse_input("18446744073709551615\n1\n+").unwrap();
+        assert!(part_01(&input).is_err());
+
+        // Too many digits for a u64 to even read
+        let input = parse_input("999999999999999999999999\n1\n+").unwrap();
+        assert!(part_01(&input).is_err());
+        assert!(part_02(&input).is_ok());
+
+        // Each problem fits, but not their sum
+        let input =
+            parse_input("18446744073709551615 1\n                   0 0\n+                    +")
+                .unwrap();
+        assert!(part_01(&input).is_err());
+
+        let input = parse_input("7\n3\n%").unwrap();
+        assert_eq!(1, part_01(&input).unwrap());
+
+        assert!(parse_input("1\n2\n&").is_err());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = parse_input("12\n3x\n\n+").err().unwrap();
+        assert_eq!(
+            "line 2: expected only digits and spaces in \"3x\"",
+            err.to_string()
+        );
+        assert!(
+            parse_input("1\n2\n\n#")
+                .err()
+                .unwrap()
+                .to_string()
+                .starts_with("line 4:")
+        );
     }
 
-    Ok(equations)
+    runner::standard_tests!(
+        day,
+        size: 20,
+        near_valid: "[0-9 +*/%|<>\\-x\\n]{0,60}",
+    );
 }
@@ -1,8 +1,9 @@
-use std::collections::{HashMap, HashSet};
+use std::{collections::HashSet, io::Write, time::Duration};
 
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use grid::Grid;
 use grid_derive::Cellable;
+use runner::{Day, Rng};
 
 type Input = Grid<Stuff>;
 
@@ -16,13 +17,35 @@ enum Stuff {
     Splitter,
 }
 
+pub fn day() -> Day {
+    Day::new(
+        7,
+        include_str!("../inputs/07/input.txt"),
+        parse_input,
+        part_01,
+        part_02,
+    )
+    .with_generator(generate)
+    .with_reference(parse_input, naive_01, naive_02)
+}
+
 fn main() -> Result<()> {
-    let input = include_str!("../inputs/07/input.txt").parse::<Grid<Stuff>>()?;
+    let day = day();
+
+    // `--visualise` animates the beams, a timeline number prints the cells that timeline goes through
+    let Some(arg) = std::env::args().nth(1) else {
+        return day.main();
+    };
+    let input = parse_input(day.input)?;
+    if arg == "--visualise" {
+        return visualise(&input, Duration::from_millis(40));
+    }
 
-    let part1 = part_01(&input);
-    println!("Part 01: {part1}");
-    let part2 = part_02(&input);
-    println!("Part 02: {part2}");
+    let n = arg.parse::<u128>()?;
+    let path = Timelines::new(&input)?
+        .path(&input, entrance(&input), n)
+        .ok_or_else(|| anyhow!("There is no timeline {n}"))?;
+    println!("Timeline {n}: {path:?}");
     Ok(())
 }
 
@@ -74,41 +97,297 @@ fn part_01(input: &Input) -> u64 {
     splits
 }
 
-fn splits(
-    point: (usize, usize),
-    grid: &Grid<Stuff>,
-    cache: &mut HashMap<(usize, usize), usize>,
-) -> usize {
-    if let Some(splits) = cache.get(&point) {
-        return *splits;
+fn parse_input(input: &str) -> Result<Input> {
+    let rows = parse::chars(input, |ch| {
+        Stuff::try_from(ch).map_err(|_| format!("unknown cell {ch:?}"))
+    })?;
+
+    let entrances = rows
+        .iter()
+        .flatten()
+        .filter(|c| **c == Stuff::Entrance)
+        .count();
+    if entrances != 1 {
+        return Err(anyhow!("Expected one entrance, found {entrances}"));
     }
 
-    let Some(c) = grid.get(point.0, point.1) else {
-        return 1;
-    };
+    // Neither part knows where a beam goes when a splitter sends it into another one
+    for (row, cells) in rows.iter().enumerate() {
+        if let Some(col) = cells
+            .windows(2)
+            .position(|pair| pair.iter().all(|c| *c == Stuff::Splitter))
+        {
+            return Err(anyhow!("Adjacent splitters at {row},{}", col + 1));
+        }
+    }
 
-    let val = if *c.val == Stuff::Splitter {
-        splits((point.0, point.1 - 1), grid, cache) + splits((point.0, point.1 + 1), grid, cache)
-    } else {
-        splits((point.0 + 1, point.1), grid, cache)
-    };
-    cache.insert(point, val);
+    let mut grid = Grid::with_capacity(rows.len());
+    for row in rows {
+        grid.add_row(row);
+    }
+    Ok(grid)
+}
 
-    val
+/// Number of timelines a beam produces from every cell of the manifold, filled bottom-up.
+struct Timelines {
+    ways: Grid<u128>,
 }
 
-fn part_02(input: &Input) -> usize {
+impl Timelines {
+    fn new(grid: &Grid<Stuff>) -> Result<Self> {
+        let cols = grid.col_count();
+        let mut rows: Vec<Vec<u128>> = Vec::with_capacity(grid.row_count());
+        for row in (0..grid.row_count()).rev() {
+            // Beams falling off the bottom are a single finished timeline each
+            let mut ways = rows.last().cloned().unwrap_or_else(|| vec![1; cols]);
+            let is_splitter = |col: usize| *grid.get(row, col).unwrap().val == Stuff::Splitter;
+            let side = |ways: &[u128], col: Option<usize>| -> Result<u128> {
+                match col.filter(|c| *c < cols) {
+                    None => Ok(1),
+                    Some(c) if is_splitter(c) => Err(anyhow!("Adjacent splitters at {row},{c}")),
+                    Some(c) => Ok(ways[c]),
+                }
+            };
+
+            for col in 0..cols {
+                if is_splitter(col) {
+                    let left = side(&ways, col.checked_sub(1))?;
+                    let right = side(&ways, Some(col + 1))?;
+                    ways[col] = left
+                        .checked_add(right)
+                        .ok_or_else(|| anyhow!("Too many timelines at {row},{col}"))?;
+                }
+            }
+            rows.push(ways);
+        }
+
+        let mut ways = Grid::with_capacity(rows.len());
+        for row in rows.into_iter().rev() {
+            ways.add_row(row);
+        }
+
+        Ok(Self { ways })
+    }
+
+    fn from(&self, row: usize, col: usize) -> u128 {
+        self.ways.get(row, col).map(|c| *c.val).unwrap_or(1)
+    }
+
+    /// Follows the `n`th timeline (left branches first) from `start`, returning every cell it crosses.
+    fn path(
+        &self,
+        grid: &Grid<Stuff>,
+        start: (usize, usize),
+        mut n: u128,
+    ) -> Option<Vec<(usize, usize)>> {
+        if n >= self.from(start.0, start.1) {
+            return None;
+        }
+
+        let mut path = vec![];
+        let (mut row, mut col) = start;
+        while let Some(c) = grid.get(row, col) {
+            path.push((row, col));
+            if *c.val != Stuff::Splitter {
+                row += 1;
+                continue;
+            }
+
+            let left = col.checked_sub(1).map(|l| self.from(row, l)).unwrap_or(1);
+            let next = if n < left {
+                col.checked_sub(1)
+            } else {
+                n -= left;
+                Some(col + 1)
+            };
+
+            let Some(next) = next.filter(|c| *c < grid.col_count()) else {
+                break;
+            };
+            col = next;
+        }
+
+        Some(path)
+    }
+}
+
+fn entrance(input: &Input) -> (usize, usize) {
     let entrance = input
         .iter_cells()
         .find(|c| *c.val == Stuff::Entrance)
         .unwrap();
-    let mut cache = HashMap::new();
-    splits((entrance.row, entrance.col), input, &mut cache)
+    (entrance.row, entrance.col)
+}
+
+/// The beams going through each row, as the number of timelines in each column.
+struct Beams {
+    rows: Vec<Vec<u128>>,
+    splits: Vec<u64>,
+}
+
+impl Beams {
+    fn new(input: &Input) -> Self {
+        let (start_row, start_col) = entrance(input);
+        let cols = input.col_count();
+        let mut rows = vec![vec![0; cols]; start_row];
+        let mut splits = vec![0; start_row];
+
+        let mut beams = vec![0u128; cols];
+        beams[start_col] = 1;
+        rows.push(beams.clone());
+        splits.push(0);
+
+        for row in (start_row + 1)..input.row_count() {
+            let mut next = vec![0u128; cols];
+            let mut split = 0;
+            for (col, count) in beams.iter().enumerate().filter(|(_, c)| **c > 0) {
+                if *input.get(row, col).unwrap().val != Stuff::Splitter {
+                    next[col] = next[col].saturating_add(*count);
+                    continue;
+                }
+
+                split += 1;
+                if col > 0 {
+                    next[col - 1] = next[col - 1].saturating_add(*count);
+                }
+                if col + 1 < cols {
+                    next[col + 1] = next[col + 1].saturating_add(*count);
+                }
+            }
+            beams = next;
+            rows.push(beams.clone());
+            splits.push(split);
+        }
+
+        Self { rows, splits }
+    }
+
+    fn frame(&self, input: &Input, upto: usize) -> String {
+        const BEAM: &str = "\x1b[1;33m";
+        const SPLIT: &str = "\x1b[1;31m";
+        const RESET: &str = "\x1b[0m";
+
+        let mut out = String::new();
+        let mut splits = 0;
+        for row in 0..input.row_count() {
+            for col in 0..input.col_count() {
+                let cell = input.get(row, col).unwrap();
+                let lit = row <= upto && self.rows[row][col] > 0;
+                let hit = row <= upto
+                    && *cell.val == Stuff::Splitter
+                    && row > 0
+                    && self.rows[row - 1][col] > 0;
+                match (lit, hit) {
+                    (_, true) => out.push_str(&format!("{SPLIT}{}{RESET}", cell.val)),
+                    (true, false) if *cell.val == Stuff::Empty => {
+                        out.push_str(&format!("{BEAM}|{RESET}"))
+                    }
+                    _ => out.push_str(&cell.val.to_string()),
+                }
+            }
+
+            if row <= upto {
+                splits += self.splits[row];
+                let timelines = self.rows[row]
+                    .iter()
+                    .fold(0u128, |acc, c| acc.saturating_add(*c));
+                out.push_str(&format!("  splits: {splits:>5}  timelines: {timelines}"));
+            }
+            out.push('\n');
+        }
+
+        out
+    }
+}
+
+fn visualise(input: &Input, delay: Duration) -> Result<()> {
+    let beams = Beams::new(input);
+    let mut stdout = std::io::stdout().lock();
+    for row in 0..input.row_count() {
+        // Clear the screen and go back to the top left before drawing the next step
+        write!(stdout, "\x1b[2J\x1b[H{}", beams.frame(input, row))?;
+        stdout.flush()?;
+        std::thread::sleep(delay);
+    }
+
+    Ok(())
+}
+
+fn part_02(input: &Input) -> Result<u128> {
+    let (row, col) = entrance(input);
+    Ok(Timelines::new(input)?.from(row, col))
+}
+
+/// Reference for part one: the splitters that some timeline goes through.
+fn naive_01(input: &Input) -> u64 {
+    let mut hit = HashSet::new();
+    follow(input, entrance(input), &mut hit);
+    hit.len() as u64
+}
+
+/// Reference for part two: follows every timeline on its own, without sharing any work.
+fn naive_02(input: &Input) -> u128 {
+    follow(input, entrance(input), &mut HashSet::new())
+}
+
+/// Drops a beam from `(row, col)`, counting the timelines it ends up as and noting every splitter
+/// it hits.
+fn follow(
+    input: &Input,
+    (mut row, col): (usize, usize),
+    hit: &mut HashSet<(usize, usize)>,
+) -> u128 {
+    loop {
+        row += 1;
+        match input.get(row, col) {
+            None => return 1,
+            Some(c) if *c.val == Stuff::Splitter => break,
+            Some(_) => {}
+        }
+    }
+
+    hit.insert((row, col));
+    let left = match col.checked_sub(1) {
+        Some(left) => follow(input, (row, left), hit),
+        None => 1,
+    };
+    let right = if col + 1 < input.col_count() {
+        follow(input, (row, col + 1), hit)
+    } else {
+        1
+    };
+    left + right
+}
+
+/// A `size` by `size` manifold with the entrance in the middle of the top row, and splitters on
+/// every other row, never next to each other.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let cols = size.max(3);
+    let mut out = String::new();
+    for row in 0..cols {
+        let mut line = vec!['.'; cols];
+        if row == 0 {
+            line[cols / 2] = 'S';
+        } else if row % 2 == 0 {
+            for col in 1..cols - 1 {
+                if line[col - 1] != '^' && rng.chance(0.3) {
+                    line[col] = '^';
+                }
+            }
+        }
+        out.extend(line);
+        out.push('\n');
+    }
+    out
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::Stuff;
+    use grid::Grid;
+
+    use crate::{Beams, Stuff, Timelines, day, entrance, parse_input, part_01, part_02};
+
+    const SAMPLE: &str = include_str!("../inputs/07/sample.txt");
 
     #[test]
     fn test_parse() {
@@ -118,4 +397,90 @@ mod tests {
         let tok: Result<Stuff, _> = 'X'.try_into();
         assert!(tok.is_err());
     }
+
+    #[test]
+    fn test_sample() {
+        let input = SAMPLE.parse::<Grid<Stuff>>().unwrap();
+        assert_eq!(21, part_01(&input));
+        assert_eq!(40, part_02(&input).unwrap());
+    }
+
+    #[test]
+    fn test_path() {
+        let input = SAMPLE.parse::<Grid<Stuff>>().unwrap();
+        let timelines = Timelines::new(&input).unwrap();
+        let start = entrance(&input);
+
+        // The first timeline always branches left
+        let first = timelines.path(&input, start, 0).unwrap();
+        assert_eq!(Some(&(0, 7)), first.first());
+        assert_eq!(Some(&(15, 0)), first.last());
+
+        let last = timelines.path(&input, start, 39).unwrap();
+        assert_eq!(Some(&(15, 14)), last.last());
+
+        assert!(timelines.path(&input, start, 40).is_none());
+    }
+
+    #[test]
+    fn test_graph() {
+        // Same count through the generic graph: splitters fork sideways, anything else falls
+        let input = SAMPLE.parse::<Grid<Stuff>>().unwrap();
+        let graph = input.to_graph(|_, cell| match cell.val {
+            Stuff::Splitter => vec![
+                ((cell.row, cell.col.wrapping_sub(1)), ()),
+                ((cell.row, cell.col + 1), ()),
+            ],
+            _ => vec![((cell.row + 1, cell.col), ())],
+        });
+
+        let (row, col) = entrance(&input);
+        let bottom = input.row_count() - 1;
+        let paths = graph.count_paths(row * input.col_count() + col, |id| {
+            graph.node(id).0 == bottom
+        });
+        assert_eq!(Some(40), paths);
+    }
+
+    #[test]
+    fn test_adjacent_splitters() {
+        let input = "..S..\n..^^.\n".parse::<Grid<Stuff>>().unwrap();
+        assert!(Timelines::new(&input).is_err());
+        assert_eq!(
+            "Adjacent splitters at 1,3",
+            parse_input("..S..\n..^^.\n").unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_beams() {
+        let input = SAMPLE.parse::<Grid<Stuff>>().unwrap();
+        let beams = Beams::new(&input);
+        assert_eq!(21, beams.splits.iter().sum::<u64>());
+        assert_eq!(40, beams.rows.last().unwrap().iter().sum::<u128>());
+
+        let frame = beams.frame(&input, 2);
+        let lines = frame.lines().collect::<Vec<_>>();
+        assert!(lines[1].contains("\x1b[1;33m|"));
+        assert!(lines[2].contains("\x1b[1;31m^"));
+        assert!(lines[2].ends_with("splits:     1  timelines: 2"));
+        assert_eq!("......^.^......", lines[4]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse_input("...\n.^.").is_err());
+        assert!(parse_input(".S.\n.^").is_err());
+        assert_eq!(
+            "line 2: column 1: unknown cell 'x' in \"x^.\"",
+            parse_input(".S.\nx^.").unwrap_err().to_string()
+        );
+    }
+
+    runner::standard_tests!(
+        day,
+        size: 30,
+        reference: (50, 14),
+        near_valid: "[.S^\\n]{0,60}",
+    );
 }
@@ -1,11 +1,12 @@
-use std::{
-    collections::{BinaryHeap, HashMap, HashSet},
-    str::FromStr,
-};
+use std::collections::{BinaryHeap, HashMap, HashSet};
 
-use anyhow::Result;
+use anyhow::{Result, anyhow};
+use grid::{Graph, NodeId};
+use grid_derive::FromLine;
+use runner::{Day, Rng};
 
-#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+#[derive(FromLine, PartialEq, Eq, Debug, Copy, Clone)]
+#[pattern("{x},{y},{z}")]
 struct Box {
     x: i64,
     y: i64,
@@ -22,30 +23,22 @@ impl Box {
     }
 }
 
-impl FromStr for Box {
-    type Err = anyhow::Error;
-
-    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        let mut parts = s.split(',');
-        Ok(Self {
-            x: parts.next().unwrap().parse()?,
-            y: parts.next().unwrap().parse()?,
-            z: parts.next().unwrap().parse()?,
-        })
-    }
-}
-
 type Input = Vec<Box>;
 
-type NodeId = usize;
+pub fn day() -> Day {
+    Day::new(
+        8,
+        include_str!("../inputs/08/input.txt"),
+        parse_input,
+        part_01,
+        part_02,
+    )
+    .with_generator(generate)
+    .with_reference(parse_input, naive_01, naive_02)
+}
 
 fn main() -> Result<()> {
-    let input = parse_input(include_str!("../inputs/08/input.txt"))?;
-    let part1 = part_01(&input);
-    println!("Part 01: {part1}");
-    let part2 = part_02(&input);
-    println!("Part 02: {part2}");
-    Ok(())
+    day().main()
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -63,17 +56,15 @@ impl Ord for Dist {
 
 impl PartialOrd for Dist {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(other.dist.cmp(&self.dist))
+        Some(self.cmp(other))
     }
 }
 
 fn part_01(input: &Input) -> usize {
     const CONNECTIONS: usize = 1000;
-    let mut circuits: Vec<HashSet<NodeId>> = vec![];
-    let mut nodes_to_circuits = HashMap::with_capacity(input.len());
     let mut distances = BinaryHeap::with_capacity(input.len() * input.len() / 2);
     for (id1, b1) in input.iter().enumerate() {
-        for (id2, b2) in (&input[(id1 + 1)..]).iter().enumerate() {
+        for (id2, b2) in input[(id1 + 1)..].iter().enumerate() {
             distances.push(Dist {
                 n1: id1,
                 n2: id1 + 1 + id2,
@@ -82,24 +73,30 @@ fn part_01(input: &Input) -> usize {
         }
     }
 
+    // The shortest connections, both ways, so that strongly connected means wired together
+    let mut graph: Graph<Box, ()> = Graph::new();
+    for b in input {
+        graph.add_node(*b);
+    }
     for _i in 0..CONNECTIONS {
         let Some(Dist { n1, n2, dist: _ }) = distances.pop() else {
             break;
         };
 
-        merge_circuits(n1, n2, &mut circuits, &mut nodes_to_circuits);
+        graph.add_edge(n1, n2, ());
+        graph.add_edge(n2, n1, ());
     }
 
-    let mut sizes = circuits
+    let mut sizes = graph
+        .strongly_connected_components()
         .iter()
-        .filter_map(|c| if c.is_empty() { None } else { Some(c.len()) })
+        .map(|c| c.len())
         .collect::<Vec<usize>>();
 
     sizes.sort();
 
-    let max = &sizes[sizes.len() - 3..];
-
-    max.iter().product()
+    // Small inputs can end up with fewer than three circuits
+    sizes.iter().rev().take(3).product()
 }
 
 type CircuitId = usize;
@@ -152,7 +149,7 @@ fn merge_circuits(
     }
     circuits[to_merge].clear();
 
-    return base;
+    base
 }
 
 fn part_02(input: &Input) -> i64 {
@@ -160,7 +157,7 @@ fn part_02(input: &Input) -> i64 {
     let mut nodes_to_circuits = HashMap::with_capacity(input.len());
     let mut distances = BinaryHeap::with_capacity(input.len() * input.len() / 2);
     for (id1, b1) in input.iter().enumerate() {
-        for (id2, b2) in (&input[(id1 + 1)..]).iter().enumerate() {
+        for (id2, b2) in input[(id1 + 1)..].iter().enumerate() {
             distances.push(Dist {
                 n1: id1,
                 n2: id1 + 1 + id2,
@@ -169,23 +166,129 @@ fn part_02(input: &Input) -> i64 {
         }
     }
 
-    loop {
-        let Some(Dist { n1, n2, dist: _ }) = distances.pop() else {
-            break;
-        };
-
+    while let Some(Dist { n1, n2, dist: _ }) = distances.pop() {
         let c = merge_circuits(n1, n2, &mut circuits, &mut nodes_to_circuits);
         if circuits[c].len() == input.len() {
             return input[n1].x * input[n2].x;
         }
     }
 
-    return 0;
+    0
+}
+
+/// Every pair of boxes, closest first.
+fn pairs(input: &Input) -> Vec<(NodeId, NodeId)> {
+    let mut pairs = (0..input.len())
+        .flat_map(|a| ((a + 1)..input.len()).map(move |b| (a, b)))
+        .collect::<Vec<_>>();
+    pairs.sort_by_key(|(a, b)| input[*a].sq_dist(&input[*b]));
+    pairs
+}
+
+/// The boxes wired to `from`, found by walking the wires.
+fn reachable(wires: &[Vec<NodeId>], from: NodeId) -> HashSet<NodeId> {
+    let mut seen = HashSet::from([from]);
+    let mut queue = vec![from];
+    while let Some(node) = queue.pop() {
+        for next in wires[node].iter() {
+            if seen.insert(*next) {
+                queue.push(*next);
+            }
+        }
+    }
+    seen
+}
+
+/// Reference for part one: walks each circuit from scratch instead of using a graph library.
+fn naive_01(input: &Input) -> usize {
+    let mut wires = vec![vec![]; input.len()];
+    for (a, b) in pairs(input).into_iter().take(1000) {
+        wires[a].push(b);
+        wires[b].push(a);
+    }
+
+    let mut seen = HashSet::new();
+    let mut sizes = vec![];
+    for node in 0..input.len() {
+        if !seen.contains(&node) {
+            let circuit = reachable(&wires, node);
+            sizes.push(circuit.len());
+            seen.extend(circuit);
+        }
+    }
+    sizes.sort();
+    sizes.iter().rev().take(3).product()
 }
 
+/// Reference for part two: adds one wire at a time and checks whether everything is connected.
+fn naive_02(input: &Input) -> i64 {
+    let mut wires = vec![vec![]; input.len()];
+    for (a, b) in pairs(input) {
+        wires[a].push(b);
+        wires[b].push(a);
+        if reachable(&wires, a).len() == input.len() {
+            return input[a].x * input[b].x;
+        }
+    }
+    0
+}
+
+/// Keeps squared distances, summed over three axes, within an `i64`.
+const MAX_COORD: i64 = 1 << 29;
+
 fn parse_input(input: &str) -> Result<Input> {
-    input
-        .lines()
-        .map(|l| l.parse::<Box>())
-        .collect::<Result<Vec<_>>>()
+    let boxes = parse::lines(input, str::parse::<Box>)?;
+    if let Some(i) = boxes
+        .iter()
+        .position(|b| [b.x, b.y, b.z].iter().any(|c| c.abs() > MAX_COORD))
+    {
+        return Err(anyhow!("Box {} is further than {MAX_COORD} away", i + 1));
+    }
+    Ok(boxes)
+}
+
+/// `size` junction boxes spread over a cube a hundred thousand units wide.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let [x, y, z] = [(); 3].map(|_| rng.below(100_000));
+            format!("{x},{y},{z}\n")
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Box, day, parse_input};
+
+    #[test]
+    fn test_parse() {
+        let input = parse_input("162,817,812\n57,618,57\n").unwrap();
+        assert_eq!(
+            Box {
+                x: 57,
+                y: 618,
+                z: 57
+            },
+            input[1]
+        );
+
+        assert_eq!(
+            "Box 2 is further than 536870912 away",
+            parse_input("1,2,3\n1,-999999999,3\n")
+                .unwrap_err()
+                .to_string()
+        );
+
+        let err = "1,2,x".parse::<Box>().unwrap_err();
+        assert_eq!(Some("z".to_string()), err.field);
+        assert!("1,2".parse::<Box>().is_err());
+    }
+
+    runner::standard_tests!(
+        day,
+        size: 50,
+        reference: (50, 60),
+        near_valid: "[0-9,\\-\\n]{0,60}",
+    );
 }
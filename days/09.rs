@@ -1,42 +1,85 @@
+use std::collections::HashMap;
+
 use anyhow::{Result, anyhow};
+use grid::Svg;
+use runner::{Day, Rng};
 
 type Input = Vec<(i64, i64)>;
 
+pub fn day() -> Day {
+    Day::new(
+        9,
+        include_str!("../inputs/09/input.txt"),
+        parse_input,
+        part_01,
+        part_02,
+    )
+    .with_generator(generate)
+    .with_reference(parse_input, naive_01, naive_02)
+}
+
 fn main() -> Result<()> {
-    let input = parse_input(include_str!("../inputs/09/input.txt"))?;
+    let day = day();
+    day.main()?;
 
-    let part1 = part_01(&input);
-    println!("Part 01: {part1}");
-    let part2 = part_02(&input);
-    println!("Part 02: {part2}");
+    // `--svg <file>` draws the tiles' polygon and the largest rectangle inside it
+    let args = std::env::args().collect::<Vec<_>>();
+    if let [_, flag, path] = args.as_slice()
+        && flag == "--svg"
+    {
+        let input = parse_input(day.input)?;
+        let mut svg = Svg::new().polygon(&input, "black", "lightgreen");
+        if let Some(rect) = largest_rect(&input) {
+            svg = svg.rect(rect.a, rect.b, "red", "none");
+        }
+        std::fs::write(path, svg.to_string())?;
+    }
     Ok(())
 }
 
+/// Keeps the area of any rectangle within a `u64`.
+const MAX_COORD: i64 = 1 << 30;
+
 fn parse_input(input: &str) -> Result<Input> {
-    input
-        .lines()
-        .map(|l| {
-            let Some((a, b)) = l.split_once(',') else {
-                return Err(anyhow!("No comma"));
-            };
-
-            Ok((a.parse()?, b.parse()?))
-        })
-        .collect::<Result<Input>>()
-}
+    let corners: Input = parse::points(input)?
+        .into_iter()
+        .map(|[x, y]| (x, y))
+        .collect();
+    if corners.len() < 4 {
+        return Err(anyhow!(
+            "Expected at least 4 corners, found {}",
+            corners.len()
+        ));
+    }
 
-fn part_01(input: &Input) -> u64 {
-    let mut max_size = 0;
-    for (i, x) in input.iter().enumerate() {
-        for y in &input[i + 1..] {
-            let a = area(x, y);
-            if a > max_size {
-                max_size = a;
-            }
-        }
+    if let Some(i) = corners
+        .iter()
+        .position(|(x, y)| x.abs() > MAX_COORD || y.abs() > MAX_COORD)
+    {
+        return Err(anyhow!("Corner {} is further than {MAX_COORD} away", i + 1));
     }
 
-    max_size
+    // Every edge, including the one closing the loop, runs along a row or a column
+    let n = corners.len();
+    if let Some(i) = (0..n).find(|i| {
+        let (a, b) = (corners[*i], corners[(i + 1) % n]);
+        a.0 != b.0 && a.1 != b.1
+    }) {
+        return Err(anyhow!(
+            "Corners {} and {} are not in line",
+            i + 1,
+            (i + 1) % n + 1
+        ));
+    }
+    Ok(corners)
+}
+
+fn part_01(input: &Input) -> u64 {
+    runner::par::pairs(input, |x, rest| rest.iter().map(|y| area(x, y)).max())
+        .into_iter()
+        .flatten()
+        .max()
+        .unwrap_or(0)
 }
 
 fn area(a: &(i64, i64), b: &(i64, i64)) -> u64 {
@@ -49,82 +92,99 @@ struct Rect {
     b: (i64, i64),
 }
 
-impl Rect {
-    fn corners(&self) -> Vec<(i64, i64)> {
-        vec![self.a, self.b, (self.a.0, self.b.1), (self.b.0, self.a.1)]
-    }
-
-    fn edges(&self) -> Vec<Segment> {
-        vec![
-            Segment::new(self.a, (self.a.0, self.b.1)),
-            Segment::new((self.a.0, self.b.1), self.b),
-            Segment::new(self.b, (self.b.0, self.a.1)),
-            Segment::new((self.b.0, self.a.1), self.a),
-        ]
-    }
+fn part_02(input: &Input) -> u64 {
+    largest_rect(input).map(|r| area(&r.a, &r.b)).unwrap_or(0)
 }
 
-fn part_02(input: &Input) -> u64 {
-    let mut max_size = 0;
+/// Every distinct coordinate, with one extra line standing for each gap between two of them.
+///
+/// Nothing changes inside a gap, so one tile of it tells about all of them.
+fn squash(coords: impl Iterator<Item = i64>) -> Vec<i64> {
+    let mut coords = coords.collect::<Vec<_>>();
+    coords.sort();
+    coords.dedup();
 
-    let mut edges = input
-        .windows(2)
-        .map(|e| e.to_vec())
-        .collect::<Vec<Vec<_>>>();
+    let mut lines = Vec::with_capacity(coords.len() * 2);
+    for (i, c) in coords.iter().enumerate() {
+        lines.push(*c);
+        if coords.get(i + 1).is_some_and(|next| next - c > 1) {
+            lines.push(c + 1);
+        }
+    }
+    lines
+}
 
-    edges.push(vec![input[input.len() - 1], input[0]]);
+fn largest_rect(input: &Input) -> Option<Rect> {
+    let segments = (0..input.len())
+        .map(|i| Segment::new(input[i], input[(i + 1) % input.len()]))
+        .collect::<Vec<_>>();
+    let mut verticals: HashMap<i64, Vec<&Segment>> = HashMap::new();
+    let mut horizontals: HashMap<i64, Vec<&Segment>> = HashMap::new();
+    for segment in segments.iter() {
+        if segment.a.0 == segment.b.0 {
+            verticals.entry(segment.a.0).or_default().push(segment);
+        } else {
+            horizontals.entry(segment.a.1).or_default().push(segment);
+        }
+    }
 
-    let edges = edges;
+    // outside[r][c] counts the squashed cells outside the polygon above and to the left of (c, r)
+    let xs = squash(input.iter().map(|p| p.0));
+    let ys = squash(input.iter().map(|p| p.1));
+    let mut outside = vec![vec![0u32; xs.len() + 1]; ys.len() + 1];
+    for (r, y) in ys.iter().enumerate() {
+        // Sweeps left to right, flipping at every vertical edge crossed
+        let mut inside = false;
+        for (c, x) in xs.iter().enumerate() {
+            let vertical = verticals.get(x).map(Vec::as_slice).unwrap_or_default();
+            let horizontal = horizontals.get(y).map(Vec::as_slice).unwrap_or_default();
+            let on_edge = vertical
+                .iter()
+                .chain(horizontal)
+                .any(|segment| segment.contains((*x, *y)));
 
-    let segments = edges
-        .iter()
-        .map(|edge| Segment::new(edge[0], edge[1]))
-        .collect::<Vec<_>>();
+            let out = u32::from(!on_edge && !inside);
+            outside[r + 1][c + 1] = out + outside[r][c + 1] + outside[r + 1][c] - outside[r][c];
 
-    for (i, x) in input.iter().enumerate() {
-        for y in &input[i + 1..] {
-            let r = Rect { a: *x, b: *y };
-            let a = area(x, y);
-            if a <= max_size {
-                continue;
+            for segment in vertical {
+                if segment.a.1 <= *y && *y < segment.b.1 {
+                    inside = !inside;
+                }
             }
+        }
+    }
 
-            if !r.corners().into_iter().all(|corner| {
-                input.iter().any(|p| *p == corner) || point_in_polygon(&edges, corner)
-            }) {
+    // The best rectangle from each corner, keeping the first of equal ones
+    let line = |lines: &[i64], v: i64| lines.binary_search(&v).unwrap();
+    let best = runner::par::pairs(input, |a, rest| {
+        let mut max_size = 0;
+        let mut best = None;
+        for b in rest {
+            let size = area(a, b);
+            if size <= max_size {
                 continue;
             }
 
-            if r.edges()
-                .into_iter()
-                .any(|corner| segments.iter().any(|segment| corner.collides_with(segment)))
-            {
-                continue;
+            let (c1, c2) = (line(&xs, a.0.min(b.0)), line(&xs, a.0.max(b.0)));
+            let (r1, r2) = (line(&ys, a.1.min(b.1)), line(&ys, a.1.max(b.1)));
+            let out = outside[r2 + 1][c2 + 1] + outside[r1][c1]
+                - outside[r1][c2 + 1]
+                - outside[r2 + 1][c1];
+            if out == 0 {
+                max_size = size;
+                best = Some((size, Rect { a: *a, b: *b }));
             }
-
-            max_size = a;
         }
-    }
-
-    max_size
-}
+        best
+    });
 
-fn point_in_polygon(edges: &[Vec<(i64, i64)>], point: (i64, i64)) -> bool {
-    // Count the number of pairs of vertical edges (where y1=y2) where y1 > y && x >= x1 && x <= x2
-    // odd means inside
-    let collisions = edges
-        .iter()
-        .filter(|edges| {
-            let (x1, x2) = if edges[0].0 < edges[1].0 {
-                (edges[0].0, edges[1].0)
-            } else {
-                (edges[1].0, edges[0].0)
-            };
-            edges[0].1 == edges[1].1 && edges[0].1 > point.1 && point.0 > x1 && point.0 <= x2
+    best.into_iter()
+        .flatten()
+        .fold(None, |best: Option<(u64, Rect)>, (size, rect)| match best {
+            Some((max, _)) if max >= size => best,
+            _ => Some((size, rect)),
         })
-        .count();
-
-    collisions % 2 != 0
+        .map(|(_, rect)| rect)
 }
 
 struct Segment {
@@ -148,43 +208,182 @@ impl Segment {
             }
         }
     }
-    fn orientation(&self) -> Orientation {
-        if self.a.0 == self.b.0 {
-            Orientation::Horizontal
-        } else {
-            Orientation::Vertical
+
+    /// Whether `point` lies on the segment, ends included.
+    fn contains(&self, point: (i64, i64)) -> bool {
+        (self.a.0..=self.b.0).contains(&point.0) && (self.a.1..=self.b.1).contains(&point.1)
+    }
+}
+
+/// Reference for part one: every pair of corners on one thread, measuring each rectangle from its
+/// edges.
+fn naive_01(input: &Input) -> u64 {
+    let mut best = 0;
+    for (i, a) in input.iter().enumerate() {
+        for b in &input[..i] {
+            let (left, right) = (a.0.min(b.0), a.0.max(b.0));
+            let (top, bottom) = (a.1.min(b.1), a.1.max(b.1));
+            best = best.max((right - left + 1) as u64 * (bottom - top + 1) as u64);
         }
     }
+    best
+}
+
+/// Reference for part two: fills in every tile of the polygon, then checks every rectangle tile by
+/// tile through running sums.
+fn naive_02(input: &Input) -> u64 {
+    let (min_x, max_x) = (
+        input.iter().map(|p| p.0).min(),
+        input.iter().map(|p| p.0).max(),
+    );
+    let (min_y, max_y) = (
+        input.iter().map(|p| p.1).min(),
+        input.iter().map(|p| p.1).max(),
+    );
+    let (Some(min_x), Some(max_x), Some(min_y), Some(max_y)) = (min_x, max_x, min_y, max_y) else {
+        return 0;
+    };
+    let width = (max_x - min_x + 1) as usize;
+    let height = (max_y - min_y + 1) as usize;
+
+    let edges = (0..input.len())
+        .map(|i| Segment::new(input[i], input[(i + 1) % input.len()]))
+        .collect::<Vec<_>>();
+    let on_edge = |x: i64, y: i64| edges.iter().any(|e| e.contains((x, y)));
+    // Casts a ray to the right, counting the vertical edges it crosses
+    let inside = |x: i64, y: i64| {
+        edges
+            .iter()
+            .filter(|e| e.a.0 == e.b.0 && e.a.0 > x && e.a.1 <= y && y < e.b.1)
+            .count()
+            % 2
+            == 1
+    };
 
-    fn collides_with(&self, other: &Self) -> bool {
-        let orientation = self.orientation();
-        let other_orientation = other.orientation();
-        if orientation == other_orientation {
-            return false;
+    // sums[y][x] counts the red or green tiles above and to the left of (x, y)
+    let mut sums = vec![vec![0u64; width + 1]; height + 1];
+    for y in 0..height {
+        for x in 0..width {
+            let (tx, ty) = (min_x + x as i64, min_y + y as i64);
+            let tile = u64::from(on_edge(tx, ty) || inside(tx, ty));
+            sums[y + 1][x + 1] = tile + sums[y][x + 1] + sums[y + 1][x] - sums[y][x];
         }
+    }
 
-        let (h, v) = if orientation == Orientation::Horizontal {
-            (self, other)
-        } else {
-            (other, self)
+    let mut best = 0;
+    for (i, a) in input.iter().enumerate() {
+        for b in &input[i + 1..] {
+            let (x1, x2) = (
+                (a.0.min(b.0) - min_x) as usize,
+                (a.0.max(b.0) - min_x) as usize,
+            );
+            let (y1, y2) = (
+                (a.1.min(b.1) - min_y) as usize,
+                (a.1.max(b.1) - min_y) as usize,
+            );
+            let tiles = sums[y2 + 1][x2 + 1] + sums[y1][x1] - sums[y1][x2 + 1] - sums[y2 + 1][x1];
+            if tiles == area(a, b) {
+                best = best.max(tiles);
+            }
+        }
+    }
+    best
+}
+
+/// A rectilinear polygon with about `size` corners: a row of columns of random heights, each
+/// overlapping the next one so that the outline doesn't cross itself.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let columns = (size / 4).max(1);
+    // Kept small enough for the reference to fill in every tile
+    let gap = 4 * columns as u64;
+    let height = 10 * gap;
+
+    let mut xs = vec![rng.range(1..=gap)];
+    let mut spans: Vec<(u64, u64)> = vec![];
+    for _ in 0..columns {
+        xs.push(xs[xs.len() - 1] + rng.range(1..=gap));
+        let (lo, hi) = match spans.last() {
+            None => (0, height),
+            Some(prev) => *prev,
         };
+        let top = rng.range(lo + 1..=height);
+        let bottom = rng.range(0..=top.min(hi) - 1);
+        spans.push((bottom, top));
+    }
 
-        h.a.0 > v.a.0 && h.a.0 < v.b.0 && h.a.1 < v.a.1 && h.b.1 > v.a.1
+    // Left to right along the top, then back along the bottom
+    let mut corners = vec![];
+    for (i, (_, top)) in spans.iter().enumerate() {
+        corners.push((xs[i], *top));
+        corners.push((xs[i + 1], *top));
+    }
+    for (i, (bottom, _)) in spans.iter().enumerate().rev() {
+        corners.push((xs[i + 1], *bottom));
+        corners.push((xs[i], *bottom));
+    }
+
+    // Equal heights side by side leave repeated or collinear corners behind
+    corners.dedup();
+    loop {
+        let n = corners.len();
+        let Some(i) = (0..n).find(|i| {
+            let (a, b, c) = (corners[(i + n - 1) % n], corners[*i], corners[(i + 1) % n]);
+            (a.0 == b.0 && b.0 == c.0) || (a.1 == b.1 && b.1 == c.1)
+        }) else {
+            break;
+        };
+        corners.remove(i);
     }
-}
 
-#[derive(Debug, PartialEq, Eq)]
-enum Orientation {
-    Horizontal,
-    Vertical,
+    corners.iter().map(|(x, y)| format!("{x},{y}\n")).collect()
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::area;
+    use crate::{area, day, parse_input, part_02};
 
     #[test]
     fn test_area() {
         assert_eq!(50, area(&(2, 5), &(11, 1)));
     }
+
+    #[test]
+    fn test_touching_outline() {
+        // A corner on the outline used to count as outside
+        let input = parse_input(
+            "10,575\n18,575\n18,627\n23,627\n23,345\n47,345\n47,330\n23,330\n23,57\n18,57\n18,290\n10,290\n",
+        )
+        .unwrap();
+        assert_eq!(3444, part_02(&input));
+
+        // An edge running along the outline used to hide the tiles outside it
+        let input = parse_input(
+            "24,570\n34,570\n34,423\n59,423\n59,433\n71,433\n71,297\n59,297\n59,307\n34,307\n34,166\n24,166\n",
+        )
+        .unwrap();
+        assert_eq!(4455, part_02(&input));
+    }
+
+    #[test]
+    fn test_parse() {
+        assert!(parse_input("1,1\n3,1\n3,3\n1,3\n").is_ok());
+        assert_eq!(
+            "Corners 2 and 3 are not in line",
+            parse_input("1,1\n3,1\n4,3\n1,3\n").unwrap_err().to_string()
+        );
+        assert!(parse_input("1,1\n3,1\n").is_err());
+        assert_eq!(
+            "Corner 2 is further than 1073741824 away",
+            parse_input("1,1\n9999999999,1\n9999999999,3\n1,3\n")
+                .unwrap_err()
+                .to_string()
+        );
+    }
+
+    runner::standard_tests!(
+        day,
+        size: 40,
+        reference: (100, 40),
+        near_valid: "[0-9,\\-\\n]{0,60}",
+    );
 }
@@ -0,0 +1,57 @@
+//! One binary for every day: `aoc run <day|all>` solves them with timings, `aoc gen <day>`
+//! prints a generated input.
+//!
+//! The days' tests refer to their items through `crate::`, so the days are only included outside
+//! of tests; they are tested as part of their own binaries.
+
+#[cfg(not(test))]
+#[allow(dead_code)]
+#[path = "../days/01.rs"]
+mod day01;
+#[cfg(not(test))]
+#[allow(dead_code)]
+#[path = "../days/02.rs"]
+mod day02;
+#[cfg(not(test))]
+#[allow(dead_code)]
+#[path = "../days/03.rs"]
+mod day03;
+#[cfg(not(test))]
+#[allow(dead_code)]
+#[path = "../days/04.rs"]
+mod day04;
+#[cfg(not(test))]
+#[allow(dead_code)]
+#[path = "../days/05.rs"]
+mod day05;
+#[cfg(not(test))]
+#[allow(dead_code)]
+#[path = "../days/06.rs"]
+mod day06;
+#[cfg(not(test))]
+#[allow(dead_code)]
+#[path = "../days/07.rs"]
+mod day07;
+#[cfg(not(test))]
+#[allow(dead_code)]
+#[path = "../days/08.rs"]
+mod day08;
+#[cfg(not(test))]
+#[allow(dead_code)]
+#[path = "../days/09.rs"]
+mod day09;
+
+#[cfg(not(test))]
+fn main() -> anyhow::Result<()> {
+    runner::cli(&[
+        day01::day(),
+        day02::day(),
+        day03::day(),
+        day04::day(),
+        day05::day(),
+        day06::day(),
+        day07::day(),
+        day08::day(),
+        day09::day(),
+    ])
+}